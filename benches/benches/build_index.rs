@@ -9,7 +9,7 @@ fn build_index() {
     index.par_load_rfcs(cb).unwrap();
     index.finish(cb);
     let path = std::path::PathBuf::from("/tmp/bench_index.json");
-    index.save(&path)
+    index.save(&path).unwrap()
 }
 
 fn criterion_benchmark(c: &mut Criterion) {
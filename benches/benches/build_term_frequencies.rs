@@ -5,7 +5,10 @@ fn build_term_frequencies(contents: String) {
     let rfc = rfsee_tf_idf::RfcEntry {
         number: 1,
         url: "https://rfsee.com".to_string(),
-        title: "RFC 8124".to_string(),
+        details: rfsee_tf_idf::RfcDetails {
+            title: "RFC 8124".to_string(),
+            ..Default::default()
+        },
         content: Some(contents),
     };
     index.add_rfc_entry(rfc);
@@ -2,13 +2,17 @@ use std::{
     ffi::{c_char, CStr},
     fs::File,
     path::PathBuf,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use rfsee_tf_idf::{
+    cache::RfcCache,
+    config::{get_config_path, Config},
     error::{RFSeeError, RFSeeResult},
-    get_index_path, search_index, Index, TfIdf,
+    fetch::{Client, FetchConfig, Proxy, RateLimit},
+    get_cache_path, get_index_path, search_index, Index, ObsoletedRfcs, RfcSource, ScoringModel,
+    SearchFilter, TfIdf, DEFAULT_BM25_B, DEFAULT_BM25_K1,
 };
 
 #[derive(Clone, Debug, Parser)]
@@ -18,17 +22,110 @@ pub struct Args {
     command: Option<Command>,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Scoring {
+    TfIdf,
+    Bm25,
+}
+
 #[derive(Clone, Debug, Subcommand)]
 enum Command {
     Index {
         #[arg(short, long)]
         path: Option<PathBuf>,
+        /// Config file, defaults to `~/.config/rfsee/config.json`
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// URL of the RFC index to fetch
+        #[arg(long)]
+        index_url: Option<String>,
+        /// Start of the URL each RFC is fetched from, followed by `{number}.txt`
+        #[arg(long)]
+        rfc_url_base: Option<String>,
+        /// Start of the URL shown for each search result, followed by `{number}.txt`
+        #[arg(long)]
+        result_url_base: Option<String>,
+        /// Build the index from a local mirror of the RFC Editor tree instead of the network
+        #[arg(long)]
+        from_dir: Option<PathBuf>,
+        /// Seconds to wait for a connection to be established
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        connect_timeout: Option<u64>,
+        /// Seconds to wait for data from an open connection
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        read_timeout: Option<u64>,
+        /// Seconds a single request, including redirects, may take in total
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        request_timeout: Option<u64>,
+        /// Maximum number of attempts for each request that fails with a transient error
+        #[arg(long)]
+        max_attempts: Option<u32>,
+        /// Maximum number of requests started per second to each host
+        #[arg(long)]
+        requests_per_second: Option<f64>,
+        /// Maximum number of requests in flight at once to each host
+        #[arg(long)]
+        max_in_flight: Option<usize>,
+        /// Update the existing index with RFCs that aren't in it yet instead of rebuilding it
+        #[arg(long)]
+        incremental: bool,
+        /// Download every RFC instead of reusing the unchanged ones cached next to the index
+        #[arg(long)]
+        no_cache: bool,
+        /// File of extra stopwords, separated by whitespace, to leave out of the index and
+        /// searches
+        #[arg(long)]
+        stopwords: Option<PathBuf>,
+        /// Stem terms so that `routing` and `routes` match each other.  Searches are stemmed the
+        /// same way.
+        #[arg(long)]
+        stem: bool,
+        /// How terms are scored, defaults to the model of the existing index when updating it and
+        /// to TF-IDF otherwise
+        #[arg(long, value_enum)]
+        scoring: Option<Scoring>,
+        /// BM25 term frequency saturation, from 0 to 10.  Defaults to 1.2.
+        #[arg(long, requires = "scoring")]
+        bm25_k1: Option<f32>,
+        /// BM25 document length normalization, from 0 for none to 1 for full.  Defaults to 0.75.
+        #[arg(long, requires = "scoring")]
+        bm25_b: Option<f32>,
     },
     Search {
         #[arg(short, long)]
         terms: String,
         #[arg(short, long)]
         index_path: Option<PathBuf>,
+        /// Config file, defaults to `~/.config/rfsee/config.json`
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Start of the URL shown for each search result, instead of the one the index was built
+        /// with
+        #[arg(long)]
+        result_url_base: Option<String>,
+        /// Only show RFCs with this status, such as `standards-track` or `informational`.  May be
+        /// repeated.
+        #[arg(long)]
+        status: Vec<String>,
+        /// Hide RFCs with this status, such as `historic`.  May be repeated.
+        #[arg(long)]
+        exclude_status: Vec<String>,
+        /// Only show RFCs from this stream, such as `ietf`.  May be repeated.
+        #[arg(long)]
+        stream: Vec<String>,
+        /// Only show RFCs published in or after this year
+        #[arg(long)]
+        from_year: Option<u16>,
+        /// Only show RFCs published in or before this year
+        #[arg(long)]
+        to_year: Option<u16>,
+        /// Hide RFCs that were obsoleted by a later one
+        #[arg(long, conflicts_with = "obsoleted_penalty")]
+        hide_obsoleted: bool,
+        /// Multiply the score of RFCs that were obsoleted by a later one by this factor, between
+        /// 0 and 1, so they rank below current RFCs
+        #[arg(long)]
+        obsoleted_penalty: Option<f32>,
     },
 }
 
@@ -46,32 +143,177 @@ extern "C" fn print_c_char(ptr: *const c_char) {
 fn handle_command(args: Args) -> RFSeeResult<()> {
     if let Some(command) = args.clone().command {
         match command {
-            Command::Index { path } => {
+            Command::Index {
+                path,
+                config,
+                index_url,
+                rfc_url_base,
+                result_url_base,
+                from_dir,
+                connect_timeout,
+                read_timeout,
+                request_timeout,
+                max_attempts,
+                requests_per_second,
+                max_in_flight,
+                incremental,
+                no_cache,
+                stopwords,
+                stem,
+                scoring,
+                bm25_k1,
+                bm25_b,
+            } => {
+                let scoring = scoring.map(|scoring| match scoring {
+                    Scoring::TfIdf => ScoringModel::TfIdf,
+                    Scoring::Bm25 => ScoringModel::Bm25 {
+                        k1: bm25_k1.unwrap_or(DEFAULT_BM25_K1),
+                        b: bm25_b.unwrap_or(DEFAULT_BM25_B),
+                    },
+                });
+                if let Some(scoring) = scoring {
+                    scoring.validate()?;
+                }
                 println!("Indexing RFCs");
                 let start = Instant::now();
-                let mut index = TfIdf::default();
-                index.par_load_rfcs(print_c_char)?;
+                let index_path = get_index_path(path)?;
+                let mut config = Config::load(&get_config_path(config)?)?;
+                config.index_url = index_url.or(config.index_url);
+                config.rfc_url_base = rfc_url_base.or(config.rfc_url_base);
+                config.result_url_base = result_url_base.or(config.result_url_base);
+                config.stopwords_file = stopwords.or(config.stopwords_file);
+                config.stem |= stem;
+                let mut index = if incremental {
+                    TfIdf::load(&index_path)?
+                } else {
+                    TfIdf::default()
+                };
+                // An update keeps the tokenizer the index was built with, unless the stopwords or
+                // stemming are set, in which case they must match it
+                if !incremental || config.stem || config.stopwords_file.is_some() {
+                    index.set_tokenizer(config.tokenizer()?)?;
+                }
+                if let Some(scoring) = scoring {
+                    index.index.scoring = scoring;
+                }
+                let mut fetch_config = FetchConfig {
+                    proxy: Proxy::from_env()?,
+                    ..Default::default()
+                };
+                if let Some(secs) = connect_timeout {
+                    fetch_config.connect_timeout = Duration::from_secs(secs);
+                }
+                if let Some(secs) = read_timeout {
+                    fetch_config.read_timeout = Duration::from_secs(secs);
+                }
+                if let Some(secs) = request_timeout {
+                    fetch_config.request_timeout = Duration::from_secs(secs);
+                }
+                if let Some(attempts) = max_attempts {
+                    fetch_config.retry.max_attempts = attempts;
+                }
+                fetch_config.rate_limit = RateLimit {
+                    requests_per_second,
+                    max_in_flight,
+                };
+                index.source = match from_dir {
+                    Some(dir) => RfcSource::LocalDir(dir),
+                    None => RfcSource::Remote(
+                        Client::new(fetch_config),
+                        config.mirror(),
+                        (!no_cache).then(|| RfcCache::new(get_cache_path(&index_path))),
+                    ),
+                };
+                let report = index.par_load_rfcs(print_c_char)?;
+                if let Some(url_base) = config.result_url_base {
+                    index.index.mirror.result_url_base = url_base;
+                }
                 println!("Loading RFCs took {:?}", start.elapsed());
+                println!("Indexed {} RFCs", report.indexed.len());
+                if !report.not_issued.is_empty() {
+                    println!(
+                        "Skipped {} RFC numbers that were not issued",
+                        report.not_issued.len()
+                    );
+                }
+                if !report.malformed.is_empty() {
+                    println!(
+                        "Skipped {} malformed index entries:",
+                        report.malformed.len()
+                    );
+                    for (entry, err) in report.malformed {
+                        let first_line = entry.lines().next().unwrap_or_default();
+                        println!("  {first_line}: {err}");
+                    }
+                }
+                if !report.skipped.is_empty() {
+                    println!("Skipped {} unavailable RFCs:", report.skipped.len());
+                    for (rfc_num, status) in report.skipped {
+                        println!("  RFC {rfc_num} (HTTP {status})");
+                    }
+                }
+                if !report.failed.is_empty() {
+                    println!("Failed to load {} RFCs:", report.failed.len());
+                    for (rfc_num, err) in report.failed {
+                        print!("  RFC {rfc_num}: {err}");
+                    }
+                }
                 let building_index_start = Instant::now();
                 index.finish(print_c_char);
                 println!("Building index took {:?}", building_index_start.elapsed());
                 let saving_start = Instant::now();
-                let index_path = get_index_path(path)?;
                 println!("Saving index");
-                index.save(&index_path);
+                index.save(&index_path)?;
                 println!("Saving index took {:?}", saving_start.elapsed());
             }
-            Command::Search { terms, index_path } => {
+            Command::Search {
+                terms,
+                index_path,
+                config,
+                result_url_base,
+                status,
+                exclude_status,
+                stream,
+                from_year,
+                to_year,
+                hide_obsoleted,
+                obsoleted_penalty,
+            } => {
+                let filter = SearchFilter {
+                    statuses: status,
+                    exclude_statuses: exclude_status,
+                    streams: stream,
+                    from_year,
+                    to_year,
+                    obsoleted: match (hide_obsoleted, obsoleted_penalty) {
+                        (true, _) => ObsoletedRfcs::Hide,
+                        (false, Some(factor)) => ObsoletedRfcs::DownRank(factor),
+                        (false, None) => ObsoletedRfcs::Show,
+                    },
+                };
+                filter.validate()?;
                 let start = Instant::now();
                 let index_path = get_index_path(index_path)?;
+                let config = Config::load(&get_config_path(config)?)?;
                 let file =
                     File::open(index_path).map_err(|e| RFSeeError::IOError(e.to_string()))?;
-                let index: Index = simd_json::from_reader(file)
+                let mut index: Index = simd_json::from_reader(file)
                     .map_err(|e| RFSeeError::ParseError(e.to_string()))?;
+                if let Some(url_base) = result_url_base.or(config.result_url_base) {
+                    index.mirror.result_url_base = url_base;
+                }
                 println!("Reading index file took: {:?}", start.elapsed());
-                let results = search_index(terms, index);
+                let results = search_index(terms, index, &filter);
                 println!("Total search time: {:?}", start.elapsed());
-                println!("Docs: {results:#?}");
+                println!("Docs:");
+                for result in results {
+                    match result.annotation() {
+                        Some(annotation) => {
+                            println!("  {} ({annotation}) - {}", result.title, result.url)
+                        }
+                        None => println!("  {} - {}", result.title, result.url),
+                    }
+                }
             }
         }
     }
@@ -1,11 +1,18 @@
-use rfsee_tf_idf::Index;
+use rfsee_tf_idf::{
+    cache::RfcCache,
+    config::{get_config_path, Config},
+    fetch::{Client, FetchConfig, Proxy, RateLimit},
+    Index, ObsoletedRfcs, RfcSource, ScoringModel, SearchFilter, DEFAULT_BM25_B, DEFAULT_BM25_K1,
+};
 use std::os::raw::c_char;
-use std::{ffi::*, fs::File};
+use std::{ffi::*, fs::File, path::PathBuf};
 
 #[repr(C)]
 pub struct RfcSearchResult {
     url: *const c_char,
     title: *const c_char,
+    /// Note to show next to the result, such as `obsoleted by RFC 9110`, or null
+    annotation: *const c_char,
 }
 
 #[repr(C)]
@@ -27,20 +34,211 @@ struct RfcSearchResultsContainer {
     cstrings: Vec<CString>,
 }
 
+/// Options for building the index.  Zero values mean no limit and null URLs use the config file
+/// or the defaults.
+#[repr(C)]
+pub struct BuildOptions {
+    /// Maximum number of requests started per second to each host
+    requests_per_second: f64,
+    /// Maximum number of requests in flight at once to each host
+    max_in_flight: i32,
+    /// URL of the RFC index
+    index_url: *const c_char,
+    /// Start of the URL each RFC is fetched from, followed by `{number}.txt`
+    rfc_url_base: *const c_char,
+    /// Start of the URL shown for each search result, followed by `{number}.txt`
+    result_url_base: *const c_char,
+    /// File of extra stopwords to leave out of the index and searches
+    stopwords_file: *const c_char,
+    /// Stem terms when not zero, so `routing` and `routes` match each other
+    stem: i32,
+    /// Scoring model, 0 for TF-IDF and 1 for BM25
+    scoring: i32,
+    /// BM25 term frequency saturation between 0 and 10, negative for the default
+    bm25_k1: f64,
+    /// BM25 document length normalization between 0 and 1, negative for the default
+    bm25_b: f64,
+}
+
+/// Filter for search results.  Statuses and streams are comma separated lists, such as
+/// `standards-track,informational`, and zero years mean no limit.  Null lists match every RFC.
+/// Obsoleted RFCs are hidden when `hide_obsoleted` isn't zero, otherwise a positive
+/// `obsoleted_penalty` multiplies their score.
+#[repr(C)]
+pub struct SearchFilterOptions {
+    /// Only include RFCs with one of these statuses
+    statuses: *const c_char,
+    /// Exclude RFCs with any of these statuses
+    exclude_statuses: *const c_char,
+    /// Only include RFCs from one of these streams
+    streams: *const c_char,
+    /// Only include RFCs published in or after this year
+    from_year: i32,
+    /// Only include RFCs published in or before this year
+    to_year: i32,
+    /// Leave RFCs obsoleted by a later one out of the results
+    hide_obsoleted: i32,
+    /// Factor, above 0 and at most 1, the score of RFCs obsoleted by a later one is multiplied
+    /// by.  0 leaves their score unchanged.
+    obsoleted_penalty: f64,
+}
+
+impl SearchFilterOptions {
+    /// # Safety
+    ///
+    /// The strings must either be null or valid nul terminated strings
+    unsafe fn to_filter(&self) -> SearchFilter {
+        let list = |ptr| {
+            unsafe { optional_string(ptr) }
+                .map(|s| {
+                    s.split(',')
+                        .map(|v| v.trim().to_string())
+                        .filter(|v| !v.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        SearchFilter {
+            statuses: list(self.statuses),
+            exclude_statuses: list(self.exclude_statuses),
+            streams: list(self.streams),
+            from_year: u16::try_from(self.from_year).ok().filter(|y| *y > 0),
+            to_year: u16::try_from(self.to_year).ok().filter(|y| *y > 0),
+            obsoleted: if self.hide_obsoleted != 0 {
+                ObsoletedRfcs::Hide
+            } else if self.obsoleted_penalty != 0.0 {
+                ObsoletedRfcs::DownRank(self.obsoleted_penalty as f32)
+            } else {
+                ObsoletedRfcs::Show
+            },
+        }
+    }
+}
+
+/// Convert a nullable C string to a `String`, returning `None` for null or invalid UTF-8
+///
+/// # Safety
+///
+/// `ptr` must either be null or point to a valid nul terminated string
+unsafe fn optional_string(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .ok()
+        .map(|s| s.to_string())
+}
+
+/// Send the message to the progress callback
+fn report(progress_cb: extern "C" fn(msg: *const c_char), msg: &str) {
+    if let Ok(cstr) = CString::new(msg.trim_end()) {
+        progress_cb(cstr.as_ptr())
+    }
+}
+
+/// Build the index and save it to the default index path.  `options` may be null to use the
+/// config file and defaults.  Errors are reported through `progress_cb`, in which case no index
+/// is saved.
+///
+/// # Safety
+///
+/// `options` must either be null or point to a valid `BuildOptions`, whose strings are either
+/// null or valid nul terminated strings
 #[no_mangle]
-pub extern "C" fn build_index(progress_cb: extern "C" fn(msg: *const c_char)) {
-    let path = rfsee_tf_idf::get_index_path(None).unwrap();
-    let mut index = rfsee_tf_idf::TfIdf::default();
-    index.par_load_rfcs(progress_cb).unwrap();
+pub unsafe extern "C" fn build_index(
+    progress_cb: extern "C" fn(msg: *const c_char),
+    options: *const BuildOptions,
+) {
+    let path = match rfsee_tf_idf::get_index_path(None) {
+        Ok(p) => p,
+        Err(e) => return report(progress_cb, &format!("Unable to get index path: {e}")),
+    };
+    let mut config = match get_config_path(None).and_then(|path| Config::load(&path)) {
+        Ok(c) => c,
+        Err(e) => return report(progress_cb, &format!("Unable to read config file: {e}")),
+    };
+    let proxy = match Proxy::from_env() {
+        Ok(p) => p,
+        Err(e) => return report(progress_cb, &format!("Invalid HTTPS_PROXY: {e}")),
+    };
+    let mut fetch_config = FetchConfig {
+        proxy,
+        ..Default::default()
+    };
+    let mut scoring = ScoringModel::default();
+    if let Some(options) = unsafe { options.as_ref() } {
+        fetch_config.rate_limit = RateLimit {
+            requests_per_second: (options.requests_per_second > 0.0)
+                .then_some(options.requests_per_second),
+            max_in_flight: (options.max_in_flight > 0).then_some(options.max_in_flight as usize),
+        };
+        config.index_url = unsafe { optional_string(options.index_url) }.or(config.index_url);
+        config.rfc_url_base =
+            unsafe { optional_string(options.rfc_url_base) }.or(config.rfc_url_base);
+        config.result_url_base =
+            unsafe { optional_string(options.result_url_base) }.or(config.result_url_base);
+        config.stopwords_file = unsafe { optional_string(options.stopwords_file) }
+            .map(PathBuf::from)
+            .or(config.stopwords_file);
+        config.stem |= options.stem != 0;
+        if options.scoring == 1 {
+            scoring = ScoringModel::Bm25 {
+                k1: if options.bm25_k1 >= 0.0 {
+                    options.bm25_k1 as f32
+                } else {
+                    DEFAULT_BM25_K1
+                },
+                b: if options.bm25_b >= 0.0 {
+                    options.bm25_b as f32
+                } else {
+                    DEFAULT_BM25_B
+                },
+            };
+        }
+    }
+    if let Err(e) = scoring.validate() {
+        return report(progress_cb, &format!("Invalid scoring model: {e}"));
+    }
+    let mut index = rfsee_tf_idf::TfIdf {
+        source: RfcSource::Remote(
+            Client::new(fetch_config),
+            config.mirror(),
+            Some(RfcCache::new(rfsee_tf_idf::get_cache_path(&path))),
+        ),
+        ..Default::default()
+    };
+    if let Err(e) = config
+        .tokenizer()
+        .and_then(|tokenizer| index.set_tokenizer(tokenizer))
+    {
+        return report(progress_cb, &format!("Unable to set up the tokenizer: {e}"));
+    }
+    index.index.scoring = scoring;
+    let load_report = match index.par_load_rfcs(progress_cb) {
+        Ok(r) => r,
+        Err(e) => return report(progress_cb, &format!("Unable to load RFCs: {e}")),
+    };
+    report(
+        progress_cb,
+        &format!(
+            "Indexed {} RFCs, skipped {} unavailable RFCs and {} malformed index entries, {} failed",
+            load_report.indexed.len(),
+            load_report.skipped.len(),
+            load_report.malformed.len(),
+            load_report.failed.len()
+        ),
+    );
     index.finish(progress_cb);
-    if let Ok(cstr) = CString::new("Saving index to disk") {
-        progress_cb(cstr.as_ptr())
+    report(progress_cb, "Saving index to disk");
+    if let Err(e) = index.save(&path) {
+        report(progress_cb, &format!("Unable to save index: {e}"));
     }
-    index.save(&path);
 }
 
 /// Search for the terms in the TF-IDF index and return the results in order with the highest
-/// scoring document first.
+/// scoring document first.  Result URLs start with `result_url_base` when it isn't null, then the
+/// one from the config file, then the one the index was built with.
 ///
 /// # Errors
 ///
@@ -56,16 +254,56 @@ pub extern "C" fn build_index(progress_cb: extern "C" fn(msg: *const c_char)) {
 ///  end of the string.
 ///
 /// * `ptr` must be [valid] for reads of bytes up to and including the nul terminator.
-///     This means in particular:
+///   This means in particular:
 ///
 /// * The entire memory range of this `CStr` must be contained within a single allocated object!
 /// * `ptr` must be non-null even for a zero-length cstr.
 /// * The memory referenced by the returned `CStr` must not be mutated for
-///     the duration of lifetime `'a`.
+///   the duration of lifetime `'a`.
 ///
 /// * The nul terminator must be within `isize::MAX` from `ptr`
+///
+/// `result_url_base` must either be null or meet the same requirements.
 #[no_mangle]
-pub unsafe extern "C" fn search_terms(terms: *const c_char) -> *mut RfcSearchResults {
+pub unsafe extern "C" fn search_terms(
+    terms: *const c_char,
+    result_url_base: *const c_char,
+) -> *mut RfcSearchResults {
+    unsafe { search(terms, result_url_base, &SearchFilter::default()) }
+}
+
+/// Search for the terms like `search_terms`, only returning the RFCs that pass the filter.
+/// `filter` may be null to return every result.
+///
+/// # Safety
+///
+/// `terms` and `result_url_base` have the same requirements as for `search_terms`.  `filter` must
+/// either be null or point to a valid `SearchFilterOptions`, whose strings are either null or
+/// valid nul terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn search_terms_filtered(
+    terms: *const c_char,
+    result_url_base: *const c_char,
+    filter: *const SearchFilterOptions,
+) -> *mut RfcSearchResults {
+    let filter = match unsafe { filter.as_ref() } {
+        Some(options) => unsafe { options.to_filter() },
+        None => SearchFilter::default(),
+    };
+    if filter.validate().is_err() {
+        return make_error_results(7);
+    }
+    unsafe { search(terms, result_url_base, &filter) }
+}
+
+/// # Safety
+///
+/// See `search_terms`
+unsafe fn search(
+    terms: *const c_char,
+    result_url_base: *const c_char,
+    filter: &SearchFilter,
+) -> *mut RfcSearchResults {
     // To convert to `CStr` the pointer must be non-null
     if terms.is_null() {
         return make_error_results(1);
@@ -82,10 +320,17 @@ pub unsafe extern "C" fn search_terms(terms: *const c_char) -> *mut RfcSearchRes
         }
     };
 
-    let index: Index = match simd_json::from_reader(file) {
+    let mut index: Index = match simd_json::from_reader(file) {
         Ok(i) => i,
         Err(_) => return make_error_results(4),
     };
+    let config = match get_config_path(None).and_then(|path| Config::load(&path)) {
+        Ok(c) => c,
+        Err(_) => return make_error_results(6),
+    };
+    if let Some(url_base) = unsafe { optional_string(result_url_base) }.or(config.result_url_base) {
+        index.mirror.result_url_base = url_base;
+    }
 
     let c_str = unsafe { CStr::from_ptr(terms) };
     let query = match c_str.to_str() {
@@ -93,12 +338,13 @@ pub unsafe extern "C" fn search_terms(terms: *const c_char) -> *mut RfcSearchRes
         Err(_) => return make_error_results(5),
     };
 
-    let search_results = rfsee_tf_idf::search_index(query.to_string(), index);
+    let search_results = rfsee_tf_idf::search_index(query.to_string(), index, filter);
 
     let mut cstrings = Vec::new();
     let mut rfc_results = Vec::with_capacity(search_results.len());
 
     for result in search_results {
+        let c_annotation = result.annotation().and_then(|a| CString::new(a).ok());
         let c_url = match CString::new(result.url) {
             Ok(c) => c,
             Err(_) => continue,
@@ -114,10 +360,18 @@ pub unsafe extern "C" fn search_terms(terms: *const c_char) -> *mut RfcSearchRes
 
         let url_ptr = cstrings[cstrings.len() - 2].as_ptr();
         let title_ptr = cstrings[cstrings.len() - 1].as_ptr();
+        let annotation_ptr = match c_annotation {
+            Some(c_annotation) => {
+                cstrings.push(c_annotation);
+                cstrings[cstrings.len() - 1].as_ptr()
+            }
+            None => std::ptr::null(),
+        };
 
         rfc_results.push(RfcSearchResult {
             url: url_ptr,
             title: title_ptr,
+            annotation: annotation_ptr,
         });
     }
 
@@ -150,6 +404,8 @@ pub unsafe extern "C" fn search_terms(terms: *const c_char) -> *mut RfcSearchRes
 /// 3 -> Unable to open index file
 /// 4 -> Unable to read index
 /// 5 -> Unable to convert search terms to CStr
+/// 6 -> Unable to read config file
+/// 7 -> Invalid search filter, such as an obsoleted penalty outside of (0, 1]
 ///
 /// # Errors
 ///
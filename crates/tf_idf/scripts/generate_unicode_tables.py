@@ -0,0 +1,69 @@
+"""Generate src/unicode_tables.rs, the data used for NFKC normalization, from the Unicode
+character database bundled with Python.
+
+    python3 scripts/generate_unicode_tables.py > src/unicode_tables.rs
+"""
+
+import unicodedata
+
+# Hangul syllables are decomposed and composed algorithmically
+HANGUL_SYLLABLES = range(0xAC00, 0xD7A4)
+SURROGATES = range(0xD800, 0xE000)
+
+
+def escape(text):
+    return "".join(f"\\u{{{ord(c):X}}}" for c in text)
+
+
+def main():
+    decompositions = []
+    compositions = []
+    classes = []
+    for cp in range(0x110000):
+        if cp in HANGUL_SYLLABLES or cp in SURROGATES:
+            continue
+        c = chr(cp)
+        decomposed = unicodedata.normalize("NFKD", c)
+        if decomposed != c:
+            decompositions.append((c, decomposed))
+        combining = unicodedata.combining(c)
+        if combining:
+            if classes and classes[-1][1] == cp - 1 and classes[-1][2] == combining:
+                classes[-1][1] = cp
+            else:
+                classes.append([cp, cp, combining])
+        # Primary composites are the canonical pairs that composition produces again, which
+        # leaves out the composition exclusions
+        decomposition = unicodedata.decomposition(c)
+        if decomposition and not decomposition.startswith("<"):
+            parts = [chr(int(p, 16)) for p in decomposition.split()]
+            if len(parts) == 2 and unicodedata.normalize("NFC", "".join(parts)) == c:
+                compositions.append((parts[0], parts[1], c))
+    compositions.sort()
+
+    print(
+        f"// Generated by `scripts/generate_unicode_tables.py` from Unicode "
+        f"{unicodedata.unidata_version}, do not edit"
+    )
+    print()
+    print("/// Ranges of characters with a non-zero canonical combining class, and the class")
+    print(f"pub static COMBINING_CLASSES: [(char, char, u8); {len(classes)}] = [")
+    for start, end, combining in classes:
+        print(f"    ('{escape(chr(start))}', '{escape(chr(end))}', {combining}),")
+    print("];")
+    print()
+    print("/// Full compatibility decomposition of each character that has one, sorted by character")
+    print(f"pub static DECOMPOSITIONS: [(char, &str); {len(decompositions)}] = [")
+    for c, decomposed in decompositions:
+        print(f"    ('{escape(c)}', \"{escape(decomposed)}\"),")
+    print("];")
+    print()
+    print("/// Pairs of characters that canonically compose into a single one, sorted by the pair")
+    print(f"pub static COMPOSITIONS: [(char, char, char); {len(compositions)}] = [")
+    for first, second, composed in compositions:
+        print(f"    ('{escape(first)}', '{escape(second)}', '{escape(composed)}'),")
+    print("];")
+
+
+if __name__ == "__main__":
+    main()
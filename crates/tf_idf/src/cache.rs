@@ -0,0 +1,149 @@
+use std::{fs::File, io::ErrorKind, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{RFSeeError, RFSeeResult},
+    fetch::Response,
+    RfcNumber,
+};
+
+/// Validators of a cached RFC, saved next to its body
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct CacheMetadata {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// An RFC body from the cache along with the validators for a conditional request
+#[derive(Debug)]
+pub struct CachedRfc {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+/// Cache of raw RFC documents, so that refreshing the index only downloads the RFCs that changed.
+/// Each RFC is stored as `rfcNNNN.txt` with its `ETag` and `Last-Modified` in `rfcNNNN.json`.
+#[derive(Clone, Debug)]
+pub struct RfcCache {
+    dir: PathBuf,
+}
+
+impl RfcCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn body_path(&self, rfc_num: RfcNumber) -> PathBuf {
+        self.dir.join(format!("rfc{rfc_num}.txt"))
+    }
+
+    fn metadata_path(&self, rfc_num: RfcNumber) -> PathBuf {
+        self.dir.join(format!("rfc{rfc_num}.json"))
+    }
+
+    /// Return the cached RFC if it was fetched from the url and can be revalidated
+    pub fn get(&self, rfc_num: RfcNumber, url: &str) -> Option<CachedRfc> {
+        let metadata_file = File::open(self.metadata_path(rfc_num)).ok()?;
+        let metadata: CacheMetadata = simd_json::from_reader(metadata_file).ok()?;
+        if metadata.url != url || (metadata.etag.is_none() && metadata.last_modified.is_none()) {
+            return None;
+        }
+        let body = std::fs::read_to_string(self.body_path(rfc_num)).ok()?;
+        Some(CachedRfc {
+            etag: metadata.etag,
+            last_modified: metadata.last_modified,
+            body,
+        })
+    }
+
+    /// Save the RFC fetched from the url.  Responses without an `ETag` or `Last-Modified` can't be
+    /// revalidated, so they aren't cached.
+    pub fn put(&self, rfc_num: RfcNumber, url: &str, response: &Response) -> RFSeeResult<()> {
+        let metadata = CacheMetadata {
+            url: url.to_string(),
+            etag: response.header("etag").map(|v| v.to_string()),
+            last_modified: response.header("last-modified").map(|v| v.to_string()),
+        };
+        if metadata.etag.is_none() && metadata.last_modified.is_none() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir).map_err(|e| RFSeeError::IOError(e.to_string()))?;
+        // An entry is only used while it has metadata, so the old metadata is removed before the
+        // body is replaced and the new metadata is written last.  That way a partially written
+        // body is never used.
+        match std::fs::remove_file(self.metadata_path(rfc_num)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                return Err(RFSeeError::IOError(e.to_string()))
+            }
+            _ => {}
+        }
+        std::fs::write(self.body_path(rfc_num), &response.body)
+            .map_err(|e| RFSeeError::IOError(e.to_string()))?;
+        let metadata_file = File::create(self.metadata_path(rfc_num))
+            .map_err(|e| RFSeeError::IOError(e.to_string()))?;
+        simd_json::to_writer(metadata_file, &metadata)
+            .map_err(|e| RFSeeError::IOError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RfcCache;
+    use crate::fetch::read_response;
+
+    #[test]
+    fn test_put_and_get() {
+        let dir = std::env::temp_dir().join("rfsee_test_cache_put_and_get");
+        let cache = RfcCache::new(dir.clone());
+        let url = "http://localhost:8080/rfc1.txt";
+        let raw = b"HTTP/1.1 200 OK\r\nETag: \"abc\"\r\nContent-Length: 5\r\n\r\nHello";
+        let response = read_response(&mut &raw[..]).unwrap();
+        cache.put(1, url, &response).unwrap();
+
+        let cached = cache.get(1, url).unwrap();
+        assert_eq!(cached.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(cached.last_modified, None);
+        assert_eq!(cached.body, "Hello");
+        // Fetched from another mirror
+        assert!(cache
+            .get(1, "https://www.rfc-editor.org/rfc/rfc1.txt")
+            .is_none());
+        assert!(cache.get(2, url).is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_put_replaces_entry() {
+        let dir = std::env::temp_dir().join("rfsee_test_cache_put_replaces_entry");
+        let cache = RfcCache::new(dir.clone());
+        let url = "http://localhost:8080/rfc1.txt";
+        let raw = b"HTTP/1.1 200 OK\r\nETag: \"abc\"\r\nContent-Length: 5\r\n\r\nHello";
+        cache
+            .put(1, url, &read_response(&mut &raw[..]).unwrap())
+            .unwrap();
+        let raw = b"HTTP/1.1 200 OK\r\nETag: \"def\"\r\nContent-Length: 5\r\n\r\nWorld";
+        cache
+            .put(1, url, &read_response(&mut &raw[..]).unwrap())
+            .unwrap();
+
+        let cached = cache.get(1, url).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+        assert_eq!(cached.etag.as_deref(), Some("\"def\""));
+        assert_eq!(cached.body, "World");
+    }
+
+    #[test]
+    fn test_responses_without_validators_are_not_cached() {
+        let dir = std::env::temp_dir().join("rfsee_test_cache_without_validators");
+        let cache = RfcCache::new(dir.clone());
+        let url = "http://localhost:8080/rfc1.txt";
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello";
+        let response = read_response(&mut &raw[..]).unwrap();
+        cache.put(1, url, &response).unwrap();
+        assert!(cache.get(1, url).is_none());
+        assert!(!dir.exists());
+    }
+}
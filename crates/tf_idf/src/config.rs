@@ -0,0 +1,149 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{RFSeeError, RFSeeResult},
+    fetch::Mirror,
+    path::home_dir,
+    tokenize::Tokenizer,
+};
+
+const CONFIG_FILE_NAME: &str = "config.json";
+
+/// Settings read from the JSON config file.  Every setting is optional and unset ones keep their
+/// defaults.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// URL of the RFC index
+    pub index_url: Option<String>,
+    /// Start of the URL each RFC document is fetched from, followed by `{number}.txt`
+    pub rfc_url_base: Option<String>,
+    /// Start of the URL shown for each search result, defaults to `rfc_url_base`
+    pub result_url_base: Option<String>,
+    /// File of extra stopwords, separated by whitespace, to leave out of the index
+    pub stopwords_file: Option<PathBuf>,
+    /// Stem terms in the index, so `routing` and `routes` match each other
+    pub stem: bool,
+}
+
+impl Config {
+    /// Load the config file, returning the default config if there is no file at the path
+    pub fn load(path: &Path) -> RFSeeResult<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let file = File::open(path).map_err(|e| RFSeeError::IOError(e.to_string()))?;
+        simd_json::from_reader(file)
+            .map_err(|e| RFSeeError::ParseError(format!("Invalid config {}: {e}", path.display())))
+    }
+
+    /// The mirror to fetch from, with any URL that isn't configured left at its default
+    pub fn mirror(&self) -> Mirror {
+        let default = Mirror::default();
+        let rfc_url_base = self.rfc_url_base.clone().unwrap_or(default.rfc_url_base);
+        Mirror {
+            index_url: self.index_url.clone().unwrap_or(default.index_url),
+            result_url_base: self
+                .result_url_base
+                .clone()
+                .unwrap_or_else(|| rfc_url_base.clone()),
+            rfc_url_base,
+        }
+    }
+
+    /// The tokenizer to index RFCs with, using the built-in stopwords and any from the
+    /// stopwords file
+    pub fn tokenizer(&self) -> RFSeeResult<Tokenizer> {
+        let mut tokenizer = Tokenizer {
+            stem: self.stem,
+            ..Default::default()
+        };
+        if let Some(path) = &self.stopwords_file {
+            tokenizer.stopwords.add_file(path)?;
+        }
+        Ok(tokenizer)
+    }
+}
+
+/// Directory holding the index and config file, `~/.config/rfsee`, which is created if it doesn't
+/// exist.  Returns `None` when there is no home directory.
+pub fn get_config_dir() -> RFSeeResult<Option<PathBuf>> {
+    let Some(home) = home_dir() else {
+        return Ok(None);
+    };
+    let rfsee_config_dir = home.join(".config/rfsee");
+    if !rfsee_config_dir.exists() {
+        std::fs::create_dir_all(&rfsee_config_dir).map_err(|e| {
+            RFSeeError::IOError(format!(
+                "Unable to create {}: {e}",
+                rfsee_config_dir.display()
+            ))
+        })?;
+    }
+    Ok(Some(rfsee_config_dir))
+}
+
+pub fn get_config_path(custom_path: Option<PathBuf>) -> RFSeeResult<PathBuf> {
+    if let Some(path) = custom_path {
+        Ok(path)
+    } else if let Some(config_dir) = get_config_dir()? {
+        Ok(config_dir.join(CONFIG_FILE_NAME))
+    } else {
+        Err(RFSeeError::IOError(
+            "No default location for the config file".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::Config;
+    use crate::fetch::{Mirror, RFC_EDITOR_URL_BASE};
+
+    #[test]
+    fn test_missing_config_is_default() {
+        let config = Config::load(Path::new("../../data/missing-config.json")).unwrap();
+        assert_eq!(config.mirror(), Mirror::default());
+    }
+
+    #[test]
+    fn test_config_mirror() {
+        let path = std::env::temp_dir().join("rfsee-test-config.json");
+        std::fs::write(
+            &path,
+            r#"{"index_url": "http://localhost:8080/rfc-index.txt", "rfc_url_base": "http://localhost:8080/rfc"}"#,
+        )
+        .unwrap();
+        let mirror = Config::load(&path).unwrap().mirror();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(mirror.index_url, "http://localhost:8080/rfc-index.txt");
+        assert_eq!(mirror.rfc_url_base, "http://localhost:8080/rfc");
+        assert_eq!(mirror.result_url_base, "http://localhost:8080/rfc");
+
+        let config = Config {
+            result_url_base: Some("https://rfc.example.com/rfc".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.mirror().rfc_url_base, RFC_EDITOR_URL_BASE);
+        assert_eq!(
+            config.mirror().result_url_base,
+            "https://rfc.example.com/rfc"
+        );
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let path = std::env::temp_dir().join("rfsee-test-invalid-config.json");
+        std::fs::write(&path, "index_url = 1").unwrap();
+        let res = Config::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(res.is_err());
+    }
+}
@@ -4,7 +4,15 @@ use std::error::Error;
 pub enum RFSeeError {
     ParseError(String),
     FetchError(String),
+    /// A non-success HTTP status code and the URL that returned it
+    HttpStatusError(u16, String),
+    /// A redirect led back to a URL that was already requested
+    RedirectLoop(String),
+    /// The redirect limit was reached, with the URL that would have been requested next
+    TooManyRedirects(String),
     IOError(String),
+    /// Connecting, reading or the whole request took longer than its timeout
+    Timeout(String),
     RuntimeError(String),
 }
 
@@ -13,7 +21,15 @@ impl std::fmt::Display for RFSeeError {
         let msg = match self {
             RFSeeError::ParseError(m) => format!("ParseError: {m}"),
             RFSeeError::FetchError(m) => format!("FetchError: {m}"),
+            RFSeeError::HttpStatusError(status, url) => {
+                format!("HttpStatusError: {status} from {url}")
+            }
+            RFSeeError::RedirectLoop(url) => format!("RedirectLoop: {url} was already requested"),
+            RFSeeError::TooManyRedirects(url) => {
+                format!("TooManyRedirects: redirect limit reached before {url}")
+            }
             RFSeeError::IOError(m) => format!("IOError: {m}"),
+            RFSeeError::Timeout(m) => format!("Timeout: {m}"),
             RFSeeError::RuntimeError(m) => format!("RuntimeError: {m}"),
         };
         writeln!(f, "RfSeeError: {msg}")
@@ -22,4 +38,17 @@ impl std::fmt::Display for RFSeeError {
 
 impl Error for RFSeeError {}
 
+impl RFSeeError {
+    /// Whether the error may go away if the request is tried again: timeouts, refused or dropped
+    /// connections, server errors (5xx) and rate limiting (429).  Other fetch errors, such as DNS
+    /// and TLS failures, would fail the same way again.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RFSeeError::Timeout(_) | RFSeeError::IOError(_) => true,
+            RFSeeError::HttpStatusError(status, _) => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+}
+
 pub type RFSeeResult<T> = Result<T, RFSeeError>;
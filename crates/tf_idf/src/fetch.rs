@@ -1,77 +1,660 @@
 use std::{
-    io::{Read, Write},
-    net::TcpStream,
+    cell::RefCell,
+    collections::{hash_map::RandomState, HashMap, HashSet},
+    hash::{BuildHasher, Hasher},
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
-use native_tls::TlsConnector;
+use native_tls::{TlsConnector, TlsStream};
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    cache::RfcCache,
     error::{RFSeeError, RFSeeResult},
-    parse::parse_rfc_details,
+    inflate::{gunzip, inflate, zlib_decompress},
+    parse::parse_rfc_citation,
+    rate_limit::{parse_retry_after, RateLimiter},
     RfcEntry,
 };
 
-const RFC_INDEX_URL: &str = "https://www.ietf.org/rfc/rfc-index.txt";
+pub use crate::{
+    proxy::Proxy,
+    rate_limit::RateLimit,
+    url::{Scheme, Url},
+};
+
+pub const RFC_INDEX_URL: &str = "https://www.ietf.org/rfc/rfc-index.txt";
 pub const RFC_EDITOR_URL_BASE: &str = "https://www.rfc-editor.org/rfc/rfc";
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const DEFAULT_JITTER: f64 = 0.5;
+/// Content encodings the client can decode, sent in `Accept-Encoding`
+const ACCEPT_ENCODING: &str = "gzip, deflate";
+/// Statuses of responses that redirect to their `Location` header
+const REDIRECT_STATUSES: [u16; 5] = [301, 302, 303, 307, 308];
+
+/// An open connection to a host, buffered for reading responses
+type Connection = BufReader<Stream>;
+
+/// The underlying stream of a connection, plain TCP for `http` and TLS for `https`
+enum Transport {
+    Plain(TcpStream),
+    Tls(TlsStream<TcpStream>),
+}
+
+impl Transport {
+    fn tcp(&self) -> &TcpStream {
+        match self {
+            Transport::Plain(stream) => stream,
+            Transport::Tls(stream) => stream.get_ref(),
+        }
+    }
+}
+
+/// A stream whose reads time out after the read timeout, or once the deadline for the current
+/// request has passed
+struct Stream {
+    inner: Transport,
+    read_timeout: Duration,
+    deadline: Option<Instant>,
+}
 
-pub fn fetch(url: &str) -> RFSeeResult<String> {
-    let parts: Vec<&str> = url.split("://").collect();
-    if parts.len() == 2 {
-        let _scheme = parts[0];
-        let remaining = parts[1];
-        let (domain, path) = match remaining.split_once("/") {
-            Some((domain, path)) => (domain, path),
-            None => {
-                return Err(RFSeeError::ParseError(
-                    "Unable to parse domain and path".to_string(),
-                ))
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let timeout = match self.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(std::io::Error::new(
+                        ErrorKind::TimedOut,
+                        "Request deadline passed",
+                    ));
+                }
+                remaining.min(self.read_timeout)
             }
+            None => self.read_timeout,
         };
-        let path_with_prefix = format!("/{path}");
-        let addr = format!("{domain}:443");
-
-        let connector = TlsConnector::new().map_err(|e| RFSeeError::FetchError(e.to_string()))?;
-        let stream =
-            TcpStream::connect(&addr).map_err(|e| RFSeeError::FetchError(e.to_string()))?;
-        let mut stream = connector
-            .connect(domain, stream)
-            .map_err(|e| RFSeeError::FetchError(e.to_string()))?;
-        let req = format!(
-        "GET {path_with_prefix} HTTP/1.1\r\nHost: {domain}\r\nUser-Agent: rfsee/0.0.1\r\nConnection: close\r\n\r\n"
-    );
-        stream
-            .write_all(req.as_bytes())
-            .map_err(|e| RFSeeError::IOError(e.to_string()))?;
-        let mut buf = String::new();
-        stream
-            .read_to_string(&mut buf)
-            .map_err(|e| RFSeeError::IOError(e.to_string()))?;
-
-        Ok(buf)
+        self.inner.tcp().set_read_timeout(Some(timeout))?;
+        match &mut self.inner {
+            Transport::Plain(stream) => stream.read(buf),
+            Transport::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.inner {
+            Transport::Plain(stream) => stream.write(buf),
+            Transport::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.inner {
+            Transport::Plain(stream) => stream.flush(),
+            Transport::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+thread_local! {
+    /// Keep-alive connections owned by the current thread, keyed by scheme, host and port
+    static CONNECTIONS: RefCell<HashMap<String, Connection>> = RefCell::new(HashMap::new());
+}
+
+/// HTTP response headers.  Keys are the lowercased header names.
+pub type Headers = HashMap<String, String>;
+
+/// An HTTP response with its status, headers and body kept separate
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Headers,
+    pub body: String,
+}
+
+impl Response {
+    /// Return the value of the header, `name` is case insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(|v| v.as_str())
+    }
+
+    /// Turn a response with a non-success (non-2xx) status into an error
+    pub fn error_for_status(self, url: &str) -> RFSeeResult<Self> {
+        if (200..300).contains(&self.status) {
+            Ok(self)
+        } else {
+            Err(RFSeeError::HttpStatusError(self.status, url.to_string()))
+        }
+    }
+}
+
+/// Convert an IO error, surfacing timed out reads and writes as `RFSeeError::Timeout`
+pub(crate) fn io_error(e: std::io::Error) -> RFSeeError {
+    match e.kind() {
+        ErrorKind::TimedOut | ErrorKind::WouldBlock => RFSeeError::Timeout(e.to_string()),
+        _ => RFSeeError::IOError(e.to_string()),
+    }
+}
+
+/// Read a single CRLF terminated line, without its line ending
+fn read_line<R: BufRead>(reader: &mut R) -> RFSeeResult<String> {
+    let mut line = Vec::new();
+    let read = reader.read_until(b'\n', &mut line).map_err(io_error)?;
+    if read == 0 {
+        return Err(RFSeeError::IOError(
+            "Connection closed before end of response".to_string(),
+        ));
+    }
+    if line.ends_with(b"\n") {
+        line.pop();
+    }
+    if line.ends_with(b"\r") {
+        line.pop();
+    }
+    String::from_utf8(line).map_err(|e| RFSeeError::ParseError(e.to_string()))
+}
+
+/// Read the status line and headers of a response
+fn read_head<R: BufRead>(reader: &mut R) -> RFSeeResult<(u16, Headers)> {
+    let status_line = read_line(reader)?;
+    let status = match status_line.split(' ').collect::<Vec<&str>>()[..] {
+        [version, code, ..] if version.starts_with("HTTP/") => code
+            .parse::<u16>()
+            .map_err(|e| RFSeeError::ParseError(format!("Invalid status code {code}: {e}")))?,
+        _ => {
+            return Err(RFSeeError::ParseError(format!(
+                "Invalid status line: {status_line}"
+            )))
+        }
+    };
+
+    let mut headers = Headers::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        match line.split_once(':') {
+            Some((name, value)) => {
+                let name = name.trim().to_ascii_lowercase();
+                let value = value.trim();
+                // Repeated headers are equivalent to a single comma separated header
+                headers
+                    .entry(name)
+                    .and_modify(|v| {
+                        v.push_str(", ");
+                        v.push_str(value)
+                    })
+                    .or_insert_with(|| value.to_string());
+            }
+            None => return Err(RFSeeError::ParseError(format!("Invalid header: {line}"))),
+        }
+    }
+    Ok((status, headers))
+}
+
+/// Read a body sent with `Transfer-Encoding: chunked`.  Each chunk is its size in hex on its own
+/// line followed by that many bytes, and a chunk of size 0 ends the body.
+fn read_chunked_body<R: BufRead>(reader: &mut R) -> RFSeeResult<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let size_line = read_line(reader)?;
+        // Chunk extensions after a `;` are ignored
+        let size = size_line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16)
+            .map_err(|e| RFSeeError::ParseError(format!("Invalid chunk size {size}: {e}")))?;
+        if size == 0 {
+            break;
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..]).map_err(io_error)?;
+        if !read_line(reader)?.is_empty() {
+            return Err(RFSeeError::ParseError(
+                "Missing line ending after chunk".to_string(),
+            ));
+        }
+    }
+    // Skip any trailer fields up to the final empty line
+    while !read_line(reader)?.is_empty() {}
+    Ok(body)
+}
+
+/// Read an HTTP/1.1 response from the reader.  The body is read according to the
+/// `Transfer-Encoding` and `Content-Length` headers, falling back to reading until the connection
+/// is closed.
+pub fn read_response<R: BufRead>(reader: &mut R) -> RFSeeResult<Response> {
+    let (mut status, mut headers) = read_head(reader)?;
+    // Skip interim responses, such as `100 Continue`
+    while (100..200).contains(&status) {
+        (status, headers) = read_head(reader)?;
+    }
+
+    let is_chunked = headers
+        .get("transfer-encoding")
+        .and_then(|v| v.split(',').next_back())
+        .is_some_and(|v| v.trim().eq_ignore_ascii_case("chunked"));
+    let content_length = match headers.get("content-length") {
+        Some(v) => Some(
+            v.parse::<usize>()
+                .map_err(|e| RFSeeError::ParseError(format!("Invalid Content-Length {v}: {e}")))?,
+        ),
+        None => None,
+    };
+
+    let mut body = Vec::new();
+    if status == 204 || status == 304 {
+        // These responses never have a body
+    } else if is_chunked {
+        body = read_chunked_body(reader)?;
+    } else if let Some(length) = content_length {
+        body.resize(length, 0);
+        reader.read_exact(&mut body).map_err(io_error)?;
     } else {
-        Err(RFSeeError::ParseError(format!("Invalid URL: {url}")))
+        reader.read_to_end(&mut body).map_err(io_error)?;
+    }
+
+    if let Some(encodings) = headers.get("content-encoding") {
+        body = decode_content(body, encodings)?;
+    }
+
+    Ok(Response {
+        status,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    })
+}
+
+/// Decode a body according to its `Content-Encoding`, undoing the encodings in the reverse of the
+/// order they were applied
+fn decode_content(mut body: Vec<u8>, encodings: &str) -> RFSeeResult<Vec<u8>> {
+    for encoding in encodings.rsplit(',') {
+        body = match encoding.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => gunzip(&body)?,
+            // `deflate` should be zlib wrapped, but some servers send a raw DEFLATE stream
+            "deflate" => zlib_decompress(&body).or_else(|_| inflate(&body))?,
+            "identity" | "" => body,
+            other => {
+                return Err(RFSeeError::ParseError(format!(
+                    "Unsupported Content-Encoding: {other}"
+                )))
+            }
+        };
+    }
+    Ok(body)
+}
+
+/// Settings for the HTTP client
+#[derive(Clone, Debug)]
+pub struct FetchConfig {
+    /// Maximum number of redirects followed for a single request
+    pub max_redirects: usize,
+    /// How long to wait for a TCP connection to be established
+    pub connect_timeout: Duration,
+    /// How long to wait for data on an open connection
+    pub read_timeout: Duration,
+    /// How long a request, including any redirects, may take in total
+    pub request_timeout: Duration,
+    /// How requests that fail with a transient error are retried
+    pub retry: RetryPolicy,
+    /// Limits on the requests made to each host
+    pub rate_limit: RateLimit,
+    /// Proxy that requests are tunnelled through, by default read from the `HTTPS_PROXY` and
+    /// `NO_PROXY` environment variables
+    pub proxy: Option<Proxy>,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry: RetryPolicy::default(),
+            rate_limit: RateLimit::default(),
+            // An invalid proxy is reported by the callers that read it with `Proxy::from_env`
+            proxy: Proxy::from_env().unwrap_or_default(),
+        }
+    }
+}
+
+/// Retry policy for requests that fail with a transient error, see `RFSeeError::is_transient`.
+/// The backoff between attempts doubles after each attempt, up to `max_backoff`.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts for a request, including the first one
+    pub max_attempts: u32,
+    /// How long to wait before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound of the wait between attempts
+    pub max_backoff: Duration,
+    /// Fraction, between 0 and 1, of each backoff that is randomized so that workers which failed
+    /// together don't all retry at the same time
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            jitter: DEFAULT_JITTER,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait after the failed attempt, counting from 1, before trying again
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)));
+        let backoff = exponential.min(self.max_backoff);
+        let jitter = self.jitter.clamp(0.0, 1.0) * random_fraction();
+        backoff.mul_f64(1.0 - jitter)
+    }
+}
+
+/// Return a random number in `[0, 1)`, using the randomly seeded hasher from the standard library
+/// so no random number generator dependency is needed
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    // Keep the 53 bits that fit in the mantissa of an `f64`
+    (random >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// HTTP client used to fetch the RFC index and RFCs.  Clones share the same rate limiter.
+#[derive(Clone, Debug)]
+pub struct Client {
+    config: FetchConfig,
+    limiter: Arc<RateLimiter>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Client::new(FetchConfig::default())
+    }
+}
+
+impl Client {
+    pub fn new(config: FetchConfig) -> Self {
+        let limiter = Arc::new(RateLimiter::new(config.rate_limit.clone()));
+        Self { config, limiter }
+    }
+
+    /// Fetch the url, following redirects and retrying transient failures according to the retry
+    /// policy.  Returns an `RFSeeError::HttpStatusError` if the final response status is not a
+    /// success, or an `RFSeeError::Timeout` if it takes longer than the configured timeouts.
+    pub fn get(&self, url: &str) -> RFSeeResult<Response> {
+        self.get_with_headers(url, &Headers::new())
+    }
+
+    /// Fetch the url like `get`, sending the extra request headers.  A `304 Not Modified` response
+    /// to a conditional request is returned rather than treated as an error.
+    pub fn get_with_headers(&self, url: &str, headers: &Headers) -> RFSeeResult<Response> {
+        let policy = &self.config.retry;
+        let mut attempt = 1;
+        loop {
+            match self.get_once(url, headers) {
+                Err(err) if err.is_transient() && attempt < policy.max_attempts => {
+                    std::thread::sleep(policy.backoff(attempt));
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// Make a single attempt at fetching the url, following redirects
+    fn get_once(&self, url: &str, headers: &Headers) -> RFSeeResult<Response> {
+        let deadline = Instant::now() + self.config.request_timeout;
+        let mut url = Url::parse(url)?;
+        let mut visited = HashSet::from([url.to_string()]);
+        for _ in 0..=self.config.max_redirects {
+            let response = self.send(&url, headers, deadline)?;
+            if response.status == 304 {
+                return Ok(response);
+            }
+            if !REDIRECT_STATUSES.contains(&response.status) {
+                return response.error_for_status(&url.to_string());
+            }
+            let location = response.header("location").ok_or_else(|| {
+                RFSeeError::FetchError(format!("Redirect from {url} without a Location"))
+            })?;
+            let next = url.join(location)?;
+            if !visited.insert(next.to_string()) {
+                return Err(RFSeeError::RedirectLoop(next.to_string()));
+            }
+            url = next;
+        }
+        Err(RFSeeError::TooManyRedirects(url.to_string()))
+    }
+
+    /// Send a single GET request for the url and return its response, whatever the status.  Open
+    /// connections are kept alive and reused by later requests from the same thread to the same
+    /// host, so each threadpool worker keeps its own connection per host.
+    fn send(&self, url: &Url, headers: &Headers, deadline: Instant) -> RFSeeResult<Response> {
+        let authority = url.authority();
+        let mut req = format!(
+            "GET {} HTTP/1.1\r\nHost: {authority}\r\nUser-Agent: rfsee/0.0.1\r\nAccept-Encoding: {ACCEPT_ENCODING}\r\n",
+            url.request_target()
+        );
+        for (name, value) in headers {
+            req.push_str(&format!("{name}: {value}\r\n"));
+        }
+        req.push_str("\r\n");
+        let key = format!("{}://{}:{}", url.scheme.as_str(), url.host, url.port());
+
+        let _permit = self.limiter.acquire(&authority);
+        let pooled = CONNECTIONS.with_borrow_mut(|conns| conns.remove(&key));
+        let (response, conn) = match pooled {
+            Some(conn) => match request(conn, &req, deadline) {
+                Ok(res) => res,
+                // The server may have closed the idle connection, so retry on a new one
+                Err(RFSeeError::IOError(_)) => request(self.connect(url)?, &req, deadline)?,
+                Err(e) => return Err(e),
+            },
+            None => request(self.connect(url)?, &req, deadline)?,
+        };
+        if can_reuse(&response) {
+            CONNECTIONS.with_borrow_mut(|conns| conns.insert(key, conn));
+        }
+        if response.status == 429 {
+            let retry_after = response.header("retry-after").and_then(parse_retry_after);
+            self.limiter.throttle(&authority, retry_after);
+        }
+        Ok(response)
+    }
+
+    /// Open a new connection to the host of the url, using TLS for `https`.  `https` connections
+    /// are tunnelled through the proxy if there is one for the host.
+    fn connect(&self, url: &Url) -> RFSeeResult<Connection> {
+        let proxy = match url.scheme {
+            Scheme::Https => self
+                .config
+                .proxy
+                .as_ref()
+                .filter(|p| p.applies_to(&url.host)),
+            Scheme::Http => None,
+        };
+        let (host, port) = match proxy {
+            Some(proxy) => (proxy.host.as_str(), proxy.port),
+            None => (url.host.as_str(), url.port()),
+        };
+        // Brackets around IPv6 addresses are only part of the URL syntax
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let addrs = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| RFSeeError::FetchError(e.to_string()))?;
+        let mut last_err = RFSeeError::FetchError(format!("No addresses found for {host}"));
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, self.config.connect_timeout) {
+                Ok(mut stream) => {
+                    // Also bounds the TLS handshake
+                    stream
+                        .set_read_timeout(Some(self.config.read_timeout))
+                        .map_err(io_error)?;
+                    stream
+                        .set_write_timeout(Some(self.config.read_timeout))
+                        .map_err(io_error)?;
+                    let inner = match url.scheme {
+                        Scheme::Http => Transport::Plain(stream),
+                        Scheme::Https => {
+                            if let Some(proxy) = proxy {
+                                proxy.tunnel(&mut stream, &url.host, url.port())?;
+                            }
+                            let connector = TlsConnector::new()
+                                .map_err(|e| RFSeeError::FetchError(e.to_string()))?;
+                            let stream = connector
+                                .connect(&url.host, stream)
+                                .map_err(|e| RFSeeError::FetchError(e.to_string()))?;
+                            Transport::Tls(stream)
+                        }
+                    };
+                    return Ok(BufReader::new(Stream {
+                        inner,
+                        read_timeout: self.config.read_timeout,
+                        deadline: None,
+                    }));
+                }
+                Err(e) if e.kind() == ErrorKind::TimedOut => {
+                    last_err = RFSeeError::Timeout(format!("Connecting to {addr} timed out"))
+                }
+                // The server may be restarting, so these are worth retrying
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        ErrorKind::ConnectionRefused
+                            | ErrorKind::ConnectionReset
+                            | ErrorKind::ConnectionAborted
+                    ) =>
+                {
+                    last_err = RFSeeError::IOError(format!("Connecting to {addr} failed: {e}"))
+                }
+                Err(e) => last_err = RFSeeError::FetchError(e.to_string()),
+            }
+        }
+        Err(last_err)
     }
 }
 
-/// Return the raw `String` contents of IETF RFC index
-pub fn fetch_rfc_index() -> RFSeeResult<String> {
-    let rfc_index_content = fetch(RFC_INDEX_URL)?;
+/// Write the raw request to the connection and read its response, handing the connection back so
+/// it can be reused
+fn request(
+    mut conn: Connection,
+    req: &str,
+    deadline: Instant,
+) -> RFSeeResult<(Response, Connection)> {
+    conn.get_mut().deadline = Some(deadline);
+    conn.get_mut().write_all(req.as_bytes()).map_err(io_error)?;
+    let response = read_response(&mut conn)?;
+    Ok((response, conn))
+}
+
+/// Whether the connection a response was read from can be used for another request.  That is only
+/// the case when the server didn't ask to close it and the end of the body was known without
+/// reading until the connection closed.
+fn can_reuse(response: &Response) -> bool {
+    let closed = response
+        .header("connection")
+        .is_some_and(|v| v.eq_ignore_ascii_case("close"));
+    let delimited = response.status == 204
+        || response.status == 304
+        || response.header("content-length").is_some()
+        || response.header("transfer-encoding").is_some();
+    !closed && delimited
+}
+
+/// Where the RFC index and RFC documents are fetched from, and the URLs shown for search results.
+/// RFC URLs are the base followed by `{number}.txt`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Mirror {
+    /// URL of the RFC index
+    pub index_url: String,
+    /// Start of the URL each RFC document is fetched from
+    pub rfc_url_base: String,
+    /// Start of the URL shown for each search result
+    pub result_url_base: String,
+}
+
+impl Default for Mirror {
+    fn default() -> Self {
+        Self {
+            index_url: RFC_INDEX_URL.to_string(),
+            rfc_url_base: RFC_EDITOR_URL_BASE.to_string(),
+            result_url_base: RFC_EDITOR_URL_BASE.to_string(),
+        }
+    }
+}
+
+/// Fetch the url with a default `Client`
+pub fn fetch(url: &str) -> RFSeeResult<Response> {
+    Client::default().get(url)
+}
+
+/// Return the raw `String` contents of the RFC index of the mirror
+pub fn fetch_rfc_index(client: &Client, mirror: &Mirror) -> RFSeeResult<String> {
+    let rfc_index_content = client.get(&mirror.index_url)?.body;
     Ok(rfc_index_content)
 }
 
-pub fn fetch_rfc(raw_rfc: &str) -> RFSeeResult<RfcEntry> {
-    if let Ok((rfc_num, title)) = parse_rfc_details(raw_rfc) {
-        let url = format!("{RFC_EDITOR_URL_BASE}{rfc_num}.txt");
-        if let Ok(content) = fetch(&url) {
-            Ok(RfcEntry {
-                number: rfc_num,
-                url: url.clone(),
-                title: title.replace("\n     ", " ").to_string(),
-                content: Some(content),
-            })
-        } else {
-            Err(RFSeeError::FetchError("Unable to fetch RFC".to_string()))
+/// Fetch the RFC for the raw index entry from the mirror.  With a cache, a conditional request is
+/// made for an RFC that is already cached and its cached body is used if it hasn't changed.
+pub fn fetch_rfc(
+    client: &Client,
+    mirror: &Mirror,
+    cache: Option<&RfcCache>,
+    raw_rfc: &str,
+) -> RFSeeResult<RfcEntry> {
+    if let Ok((rfc_num, details)) = parse_rfc_citation(raw_rfc) {
+        let url = format!("{}{rfc_num}.txt", mirror.rfc_url_base);
+        let cached = cache.and_then(|cache| cache.get(rfc_num, &url));
+        let mut headers = Headers::new();
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                headers.insert("If-None-Match".to_string(), etag.clone());
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                headers.insert("If-Modified-Since".to_string(), last_modified.clone());
+            }
         }
+        let response = client.get_with_headers(&url, &headers)?;
+        let content = match (response.status, cached) {
+            (304, Some(cached)) => cached.body,
+            (304, None) => {
+                return Err(RFSeeError::FetchError(format!(
+                    "Unexpected 304 Not Modified for {url}"
+                )))
+            }
+            _ => {
+                if let Some(cache) = cache {
+                    // The RFC was fetched, failing to cache it only costs downloading it again
+                    let _ = cache.put(rfc_num, &url, &response);
+                }
+                response.body
+            }
+        };
+        Ok(RfcEntry {
+            number: rfc_num,
+            url: url.clone(),
+            details,
+            content: Some(content),
+        })
     } else {
         Err(RFSeeError::ParseError(
             "Unable to parse raw RFC".to_string(),
@@ -87,35 +670,343 @@ mod tests {
     fn fetch_empty_path() {
         let url = "https://www.rfc-editor.org/";
         let res = fetch(url).unwrap();
-        if let Some(first_line) = res.lines().next() {
-            assert_eq!(first_line, "HTTP/1.1 200 OK")
-        }
+        assert_eq!(res.status, 200)
     }
 
     #[test]
     fn fetch_ietf_path() {
         let url = "https://www.ietf.org/rfc/rfc-index.txt";
         let res = fetch(url).unwrap();
-        if let Some(first_line) = res.lines().next() {
-            assert_eq!(first_line, "HTTP/1.1 200 OK")
-        }
+        assert_eq!(res.status, 200);
+        assert!(!res.body.starts_with("HTTP/1.1"))
     }
 
     #[test]
     fn fetch_rfc() {
         let url = "https://www.rfc-editor.org/rfc/rfc8124.txt";
         let res = fetch(url).unwrap();
-        if let Some(first_line) = res.lines().next() {
-            assert_eq!(first_line, "HTTP/1.1 200 OK")
-        }
+        assert_eq!(res.status, 200)
     }
 
     #[test]
     fn fetch_nonexistent_rfc() {
         let url = "https://www.rfc-editor.org/rfc/rfc999999999.txt";
-        let res = fetch(url).unwrap();
-        if let Some(first_line) = res.lines().next() {
-            assert_eq!(first_line, "HTTP/1.1 404 Not Found")
+        let res = fetch(url);
+        assert!(matches!(res, Err(RFSeeError::HttpStatusError(404, _))))
+    }
+
+    #[test]
+    fn read_response_separates_body() {
+        let raw =
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nServer: nginx\r\n\r\nHello\r\nworld";
+        let res = read_response(&mut &raw[..]).unwrap();
+        assert_eq!(res.status, 200);
+        assert_eq!(res.header("content-type"), Some("text/plain"));
+        assert_eq!(res.header("Server"), Some("nginx"));
+        assert_eq!(res.body, "Hello\r\nworld");
+    }
+
+    #[test]
+    fn error_for_non_success_status() {
+        let raw = b"HTTP/1.1 503 Service Unavailable\r\nRetry-After: 10\r\n\r\nSlow down";
+        let res = read_response(&mut &raw[..])
+            .unwrap()
+            .error_for_status("https://www.rfsee.com/1");
+        assert!(matches!(res, Err(RFSeeError::HttpStatusError(503, _))));
+    }
+
+    #[test]
+    fn read_response_invalid_status_line() {
+        let raw = b"Hello world\r\n\r\n";
+        assert!(read_response(&mut &raw[..]).is_err());
+    }
+
+    #[test]
+    fn read_response_chunked_body() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nHello \r\n5;ext=1\r\nworld\r\n0\r\nExpires: never\r\n\r\n";
+        let res = read_response(&mut &raw[..]).unwrap();
+        assert_eq!(res.body, "Hello world");
+    }
+
+    #[test]
+    fn read_response_content_length() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHelloHTTP/1.1 200 OK";
+        let mut reader = &raw[..];
+        let res = read_response(&mut reader).unwrap();
+        assert_eq!(res.body, "Hello");
+        // The rest of the stream is left for the next response
+        assert_eq!(reader, b"HTTP/1.1 200 OK");
+    }
+
+    #[test]
+    fn read_response_decodes_content_encoding() {
+        let gzipped = include_bytes!("../../../data/compressed/rfc1.txt.gz");
+        let mut raw = format!(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+            gzipped.len()
+        )
+        .into_bytes();
+        raw.extend_from_slice(gzipped);
+        let res = read_response(&mut &raw[..]).unwrap();
+        let expected = std::fs::read_to_string("../../data/mirror/rfc1.txt").unwrap();
+        assert_eq!(res.body, expected);
+
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Encoding: br\r\n\r\nHello";
+        assert!(read_response(&mut &raw[..]).is_err());
+    }
+
+    #[test]
+    fn read_response_truncated_body() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 50\r\n\r\nHello";
+        assert!(read_response(&mut &raw[..]).is_err());
+    }
+
+    /// Serve one request per raw response on a local port, each on its own connection, returning
+    /// the port and the requests that were received
+    fn serve(responses: Vec<&'static str>) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let mut reqs = Vec::new();
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut req = String::new();
+                while !req.ends_with("\r\n\r\n") {
+                    reader.read_line(&mut req).unwrap();
+                }
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+                reqs.push(req);
+            }
+            reqs
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn fetch_plain_http_with_port() {
+        let (port, handle) = serve(vec!["HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello"]);
+        let res = fetch(&format!("http://127.0.0.1:{port}/rfc-index.txt?x=y")).unwrap();
+        assert_eq!(res.body, "Hello");
+        let req = &handle.join().unwrap()[0];
+        assert!(req.starts_with("GET /rfc-index.txt?x=y HTTP/1.1\r\n"));
+        assert!(req.contains("Accept-Encoding: gzip, deflate\r\n"));
+        assert!(req.contains(&format!("Host: 127.0.0.1:{port}\r\n")));
+    }
+
+    #[test]
+    fn follow_redirect() {
+        let (port, handle) = serve(vec![
+            "HTTP/1.1 301 Moved Permanently\r\nLocation: /new\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nHello",
+        ]);
+        let res = fetch(&format!("http://127.0.0.1:{port}/old")).unwrap();
+        assert_eq!(res.status, 200);
+        assert_eq!(res.body, "Hello");
+        let reqs = handle.join().unwrap();
+        assert!(reqs[0].starts_with("GET /old HTTP/1.1\r\n"));
+        assert!(reqs[1].starts_with("GET /new HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn redirect_to_visited_url_is_a_loop() {
+        let (port, handle) = serve(vec![
+            "HTTP/1.1 302 Found\r\nLocation: /loop\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let res = fetch(&format!("http://127.0.0.1:{port}/loop"));
+        assert!(matches!(res, Err(RFSeeError::RedirectLoop(url)) if url.ends_with("/loop")));
+        assert_eq!(handle.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn redirects_past_limit_are_too_many() {
+        let (port, handle) = serve(vec![
+            "HTTP/1.1 302 Found\r\nLocation: /2\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 302 Found\r\nLocation: /3\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 302 Found\r\nLocation: /4\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let client = Client::new(FetchConfig {
+            max_redirects: 2,
+            ..Default::default()
+        });
+        let res = client.get(&format!("http://127.0.0.1:{port}/1"));
+        assert!(matches!(res, Err(RFSeeError::TooManyRedirects(url)) if url.ends_with("/4")));
+        assert_eq!(handle.join().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn redirect_without_location() {
+        let (port, _) = serve(vec![
+            "HTTP/1.1 301 Moved Permanently\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let res = fetch(&format!("http://127.0.0.1:{port}/old"));
+        assert!(
+            matches!(res, Err(RFSeeError::FetchError(msg)) if msg.contains("without a Location"))
+        );
+    }
+
+    #[test]
+    fn fetch_rfc_revalidates_cached_body() {
+        let (port, handle) = serve(vec![
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 5\r\nConnection: close\r\n\r\nHello",
+            "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+        ]);
+        let dir = std::env::temp_dir().join("rfsee_test_fetch_rfc_revalidates_cached_body");
+        let cache = RfcCache::new(dir.clone());
+        let mirror = Mirror {
+            rfc_url_base: format!("http://127.0.0.1:{port}/rfc"),
+            ..Default::default()
+        };
+        let client = Client::default();
+        let raw_rfc = "0001 Host Software. S. Crocker. April 1969.";
+
+        let rfc = super::fetch_rfc(&client, &mirror, Some(&cache), raw_rfc).unwrap();
+        assert_eq!(rfc.content.as_deref(), Some("Hello"));
+        let rfc = super::fetch_rfc(&client, &mirror, Some(&cache), raw_rfc).unwrap();
+        assert_eq!(rfc.content.as_deref(), Some("Hello"));
+
+        let reqs = handle.join().unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+        assert!(!reqs[0].contains("If-None-Match"));
+        assert!(reqs[1].contains("If-None-Match: \"v1\"\r\n"));
+    }
+
+    #[test]
+    fn fetch_unsupported_scheme() {
+        let res = fetch("ftp://ftp.rfc-editor.org/in-notes/rfc1.txt");
+        assert!(matches!(res, Err(RFSeeError::ParseError(_))));
+    }
+
+    #[test]
+    fn reuse_delimited_responses() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello";
+        assert!(can_reuse(&read_response(&mut &raw[..]).unwrap()));
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nHello";
+        assert!(!can_reuse(&read_response(&mut &raw[..]).unwrap()));
+        let raw = b"HTTP/1.1 200 OK\r\n\r\nHello";
+        assert!(!can_reuse(&read_response(&mut &raw[..]).unwrap()));
+    }
+
+    #[test]
+    fn reuse_connection_until_server_closes_it() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Answer two requests on the first connection then close it, and one on the next
+        let handle = std::thread::spawn(move || {
+            let mut requests_per_connection = Vec::new();
+            for requests in [2, 1] {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                for _ in 0..requests {
+                    let mut req = String::new();
+                    while !req.ends_with("\r\n\r\n") {
+                        reader.read_line(&mut req).unwrap();
+                    }
+                    reader
+                        .get_mut()
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello")
+                        .unwrap();
+                }
+                requests_per_connection.push(requests);
+            }
+            requests_per_connection
+        });
+        // A request sent on a new connection while the server waits on the first one times out
+        let client = Client::new(FetchConfig {
+            read_timeout: Duration::from_secs(2),
+            retry: RetryPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let url = format!("http://127.0.0.1:{port}/rfc1.txt");
+        for _ in 0..3 {
+            assert_eq!(client.get(&url).unwrap().body, "Hello");
+        }
+        assert_eq!(handle.join().unwrap(), vec![2, 1]);
+    }
+
+    #[test]
+    fn timed_out_reads_are_timeout_errors() {
+        let err = io_error(std::io::Error::new(
+            ErrorKind::WouldBlock,
+            "Resource unavailable",
+        ));
+        assert!(matches!(err, RFSeeError::Timeout(_)));
+        let err = io_error(std::io::Error::new(ErrorKind::UnexpectedEof, "Early eof"));
+        assert!(matches!(err, RFSeeError::IOError(_)));
+    }
+
+    #[test]
+    fn stalled_server_times_out() {
+        // The connection is accepted by the listener's backlog but the request is never answered
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = Client::new(FetchConfig {
+            read_timeout: Duration::from_millis(200),
+            retry: RetryPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let start = Instant::now();
+        let err = client
+            .get(&format!("http://127.0.0.1:{port}/rfc1.txt"))
+            .unwrap_err();
+        assert!(matches!(err, RFSeeError::Timeout(_)));
+        assert!(start.elapsed() < Duration::from_secs(2));
+        drop(listener);
+    }
+
+    #[test]
+    fn refused_connections_are_retried() {
+        // Nothing listens on the port once the listener is dropped
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let client = Client::new(FetchConfig {
+            retry: RetryPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let err = client
+            .get(&format!("http://127.0.0.1:{port}/"))
+            .unwrap_err();
+        assert!(matches!(err, RFSeeError::IOError(_)));
+        assert!(err.is_transient());
+        assert!(!RFSeeError::FetchError("Unknown host".to_string()).is_transient());
+    }
+
+    #[test]
+    fn retry_backoff_doubles_up_to_max() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.0,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn retry_backoff_jitter_stays_in_range() {
+        let policy = RetryPolicy {
+            jitter: 0.5,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let backoff = policy.backoff(2);
+            assert!(backoff <= Duration::from_secs(2));
+            assert!(backoff >= Duration::from_secs(1));
         }
     }
 }
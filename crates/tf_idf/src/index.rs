@@ -1,19 +1,22 @@
 use std::{
     collections::HashMap,
     ffi::{c_char, CString},
+    fs::File,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
     time::Duration,
 };
 
 use crate::{
+    cache::RfcCache,
+    config::get_config_dir,
     error::{RFSeeError, RFSeeResult},
-    fetch::{fetch, fetch_rfc, fetch_rfc_index, RFC_EDITOR_URL_BASE},
-    parse::{parse_rfc_details, parse_rfc_index},
-    path::home_dir,
+    fetch::{fetch_rfc, fetch_rfc_index, Client, Mirror},
+    mirror::{read_rfc, read_rfc_index},
+    parse::{clean_rfc_text, parse_rfc_citation, parse_rfc_details, parse_rfc_index},
     threadpool,
+    tokenize::Tokenizer,
 };
-use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 /// A term in a document
@@ -40,30 +43,113 @@ pub type TermScore = i32;
 pub type RfcDetailsMap = HashMap<RfcNumber, RfcDetails>;
 
 const RFC_EDITOR_FILE_TYPE: &str = "txt";
-const WORD_MATCH_REGEX: &str = r"(\w+)";
 /// We have an epsilon value to account for some terms, like "HTTP", being in all RFCs.
 const EPSILON: f32 = 0.0001;
-/// How to split the user provided search
-const SEARCH_TERMS_DELIMITER: &str = " ";
+/// TF-IDF scores are small fractions, they are scaled up before being rounded to integers
+const TF_IDF_SCORE_SCALE: f32 = 1_000_000_000.0;
+/// BM25 scores are larger than TF-IDF ones and are summed over the query terms, so they are
+/// scaled less to stay within an `i32`
+const BM25_SCORE_SCALE: f32 = 1_000_000.0;
+/// Term frequency saturation commonly used for BM25
+pub const DEFAULT_BM25_K1: f32 = 1.2;
+/// Document length normalization commonly used for BM25
+pub const DEFAULT_BM25_B: f32 = 0.75;
+/// Largest BM25 term frequency saturation, which keeps the scaled score of a term within an `i32`
+pub const MAX_BM25_K1: f32 = 10.0;
 const INDEX_FILE_NAME: &str = "index.json";
+/// Extension of the file, saved next to the index, holding the per document state needed to
+/// rescore the index when it is updated
+const STATE_FILE_EXTENSION: &str = "state.json";
+/// Extension of the directory, next to the index, caching the raw RFC documents
+const CACHE_DIR_EXTENSION: &str = "cache";
 const DEFAULT_INDEX_PATH: &str = "/tmp/index.json";
+/// Status filter matching every status on the standards track
+const STANDARDS_TRACK: &str = "STANDARDS TRACK";
+const STANDARDS_TRACK_STATUSES: [&str; 3] =
+    ["PROPOSED STANDARD", "DRAFT STANDARD", "INTERNET STANDARD"];
 
 #[derive(Debug)]
 pub struct RfcEntry {
     pub number: i32,
     pub url: String,
-    pub title: String,
+    pub details: RfcDetails,
     pub content: Option<String>,
 }
 
+#[derive(Deserialize, Serialize)]
 pub struct ProcessedRfc {
     number: i32,
     term_freqs: TermFreqs,
+    /// Number of terms in the document
+    #[serde(default)]
+    length: usize,
+}
+
+/// How the score of a term in each RFC is computed when the index is built
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum ScoringModel {
+    /// The frequency of the term in the RFC times its inverse document frequency
+    #[default]
+    TfIdf,
+    /// Okapi BM25, where `k1` limits how much repeating a term adds to the score and `b` how much
+    /// scores are normalized by the length of the RFC, from 0 for not at all to 1 for fully
+    Bm25 { k1: f32, b: f32 },
+}
+
+impl ScoringModel {
+    /// BM25 with the commonly used parameters
+    pub fn bm25() -> Self {
+        ScoringModel::Bm25 {
+            k1: DEFAULT_BM25_K1,
+            b: DEFAULT_BM25_B,
+        }
+    }
+
+    /// Check the BM25 parameters, `k1` must be between 0 and `MAX_BM25_K1` and `b` between 0
+    /// and 1
+    pub fn validate(&self) -> RFSeeResult<()> {
+        match *self {
+            ScoringModel::Bm25 { k1, .. } if !(0.0..=MAX_BM25_K1).contains(&k1) => {
+                Err(RFSeeError::ParseError(format!(
+                    "BM25 k1 must be between 0 and {MAX_BM25_K1}, not {k1}"
+                )))
+            }
+            ScoringModel::Bm25 { b, .. } if !(0.0..=1.0).contains(&b) => Err(
+                RFSeeError::ParseError(format!("BM25 b must be between 0 and 1, not {b}")),
+            ),
+            _ => Ok(()),
+        }
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Metadata of an RFC from its citation in the RFC index
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
 pub struct RfcDetails {
-    title: String,
+    pub title: String,
+    pub authors: Vec<String>,
+    /// Month of publication, from 1 for January
+    pub month: Option<u8>,
+    pub year: Option<u16>,
+    /// Formats the RFC is published in, such as `TXT` and `HTML`
+    pub formats: Vec<String>,
+    /// Such as `PROPOSED STANDARD` or `INFORMATIONAL`
+    pub status: Option<String>,
+    /// Such as `IETF` or `IAB`
+    pub stream: Option<String>,
+    pub doi: Option<String>,
+    /// RFCs this one replaces
+    pub obsoletes: Vec<RfcNumber>,
+    /// RFCs that replaced this one
+    pub obsoleted_by: Vec<RfcNumber>,
+    /// RFCs this one updates
+    pub updates: Vec<RfcNumber>,
+    /// RFCs that updated this one
+    pub updated_by: Vec<RfcNumber>,
+    /// The equivalent documents in other series, such as `STD0007` or `BCP0014`
+    pub also: Vec<String>,
+    /// The RFC number was never issued, so there is no document for it
+    pub not_issued: bool,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -71,6 +157,15 @@ pub struct Index {
     pub rfc_details: RfcDetailsMap,
     /// Map of terms to map of RFC numbers with that term and the terms score in that RFC
     pub term_scores: HashMap<Term, HashMap<RfcNumber, TermScore>>,
+    /// The mirror the index was built from, whose result URL base is used for search results
+    #[serde(default)]
+    pub mirror: Mirror,
+    /// How the RFCs were tokenized, which queries are tokenized the same way as
+    #[serde(default)]
+    pub tokenizer: Tokenizer,
+    /// How the term scores were computed
+    #[serde(default)]
+    pub scoring: ScoringModel,
 }
 
 #[repr(C)]
@@ -78,16 +173,109 @@ pub struct Index {
 pub struct RfcSearchResult {
     pub url: String,
     pub title: String,
+    /// RFCs that replaced this one
+    pub obsoleted_by: Vec<RfcNumber>,
+}
+
+impl RfcSearchResult {
+    /// Note to show next to the result, such as `obsoleted by RFC 9110`
+    pub fn annotation(&self) -> Option<String> {
+        if self.obsoleted_by.is_empty() {
+            return None;
+        }
+        let rfcs: Vec<String> = self
+            .obsoleted_by
+            .iter()
+            .map(|n| format!("RFC {n}"))
+            .collect();
+        Some(format!("obsoleted by {}", rfcs.join(", ")))
+    }
+}
+
+/// Summary of which RFCs were loaded into the index
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    /// RFCs that were loaded and added to the index
+    pub indexed: Vec<RfcNumber>,
+    /// RFC numbers that were never issued, which are recorded in the index but not fetched
+    pub not_issued: Vec<RfcNumber>,
+    /// Entries of the RFC index that couldn't be parsed, along with the parse error
+    pub malformed: Vec<(String, RFSeeError)>,
+    /// RFCs that weren't indexed because fetching their document returned a non-success HTTP
+    /// status that isn't transient, such as a 404, along with that status
+    pub skipped: Vec<(RfcNumber, u16)>,
+    /// RFCs that weren't indexed because loading their document still failed after any retries,
+    /// along with the last error
+    pub failed: Vec<(RfcNumber, RFSeeError)>,
+}
+
+impl LoadReport {
+    /// Record the RFC whose document couldn't be loaded because of the error
+    fn record_error(&mut self, rfc_num: RfcNumber, err: RFSeeError) {
+        match err {
+            RFSeeError::HttpStatusError(status, _) if !err.is_transient() => {
+                self.skipped.push((rfc_num, status))
+            }
+            err => self.failed.push((rfc_num, err)),
+        }
+    }
+}
+
+/// Where the RFC index and RFC documents are loaded from
+// There is only ever one source per index so the size of the variants doesn't matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+pub enum RfcSource {
+    /// Fetch everything from the mirror, by default the IETF and RFC Editor websites, with the
+    /// client.  RFCs in the cache are only downloaded again if they changed.
+    Remote(Client, Mirror, Option<RfcCache>),
+    /// Read `rfc-index.txt` and `rfcNNNN.txt` files from a local mirror of the RFC Editor tree
+    LocalDir(PathBuf),
+}
+
+impl Default for RfcSource {
+    fn default() -> Self {
+        RfcSource::Remote(Client::default(), Mirror::default(), None)
+    }
+}
+
+impl RfcSource {
+    /// Return the raw `String` contents of the RFC index
+    pub fn rfc_index(&self) -> RFSeeResult<String> {
+        match self {
+            RfcSource::Remote(client, mirror, _) => fetch_rfc_index(client, mirror),
+            RfcSource::LocalDir(dir) => read_rfc_index(dir),
+        }
+    }
+
+    /// Load the document for a raw RFC index entry
+    pub fn rfc(&self, raw_rfc: &str) -> RFSeeResult<RfcEntry> {
+        match self {
+            RfcSource::Remote(client, mirror, cache) => {
+                fetch_rfc(client, mirror, cache.as_ref(), raw_rfc)
+            }
+            RfcSource::LocalDir(dir) => read_rfc(dir, raw_rfc),
+        }
+    }
+
+    /// The mirror RFCs are loaded from, which is recorded in the index.  For a local directory the
+    /// URLs are the paths of the files and results link to the RFC Editor.
+    pub fn mirror(&self) -> Mirror {
+        match self {
+            RfcSource::Remote(_, mirror, _) => mirror.clone(),
+            RfcSource::LocalDir(dir) => Mirror {
+                index_url: dir.join("rfc-index.txt").display().to_string(),
+                rfc_url_base: dir.join("rfc").display().to_string(),
+                ..Default::default()
+            },
+        }
+    }
 }
 
 pub fn get_index_path(custom_path: Option<PathBuf>) -> RFSeeResult<PathBuf> {
     if let Some(path) = custom_path {
         Ok(path)
-    } else if let Some(home_dir) = home_dir() {
-        let rfsee_config_dir = home_dir.join(".config/rfsee");
-        if !rfsee_config_dir.exists() {
-            std::fs::create_dir_all(&rfsee_config_dir).unwrap()
-        }
+    } else if let Some(rfsee_config_dir) = get_config_dir()? {
         Ok(rfsee_config_dir.join(INDEX_FILE_NAME))
     } else if cfg!(unix) {
         Ok(PathBuf::from(DEFAULT_INDEX_PATH))
@@ -98,6 +286,16 @@ pub fn get_index_path(custom_path: Option<PathBuf>) -> RFSeeResult<PathBuf> {
     }
 }
 
+/// Path of the file holding the per document state for the index at `index_path`
+pub fn get_state_path(index_path: &Path) -> PathBuf {
+    index_path.with_extension(STATE_FILE_EXTENSION)
+}
+
+/// Path of the directory caching the raw RFC documents for the index at `index_path`
+pub fn get_cache_path(index_path: &Path) -> PathBuf {
+    index_path.with_extension(CACHE_DIR_EXTENSION)
+}
+
 #[repr(C)]
 #[derive(Default)]
 pub struct TfIdf {
@@ -112,46 +310,128 @@ pub struct TfIdf {
     pub docs_with_term: DocsWithTerm,
     pub processed_rfcs: ProcessedRfcs,
     pub index: Index,
+    /// Where RFCs are loaded from
+    pub source: RfcSource,
 }
 
 impl TfIdf {
-    pub fn load_rfcs(&mut self) -> RFSeeResult<()> {
-        let raw_rfc_index = fetch_rfc_index()?;
+    /// Load a previously saved index, along with the per document state needed to rescore it, so
+    /// that it can be updated with RFCs published since it was built
+    pub fn load(path: &Path) -> RFSeeResult<Self> {
+        let index_file = File::open(path).map_err(|e| RFSeeError::IOError(e.to_string()))?;
+        let index: Index = simd_json::from_reader(index_file)
+            .map_err(|e| RFSeeError::ParseError(e.to_string()))?;
+        let state_path = get_state_path(path);
+        let state_file = File::open(&state_path).map_err(|e| {
+            RFSeeError::IOError(format!(
+                "Unable to open index state {}, a full index is required: {e}",
+                state_path.display()
+            ))
+        })?;
+        let processed_rfcs: ProcessedRfcs = simd_json::from_reader(state_file)
+            .map_err(|e| RFSeeError::ParseError(e.to_string()))?;
+        Ok(Self {
+            index,
+            processed_rfcs,
+            ..Default::default()
+        })
+    }
+
+    /// Set how RFCs are tokenized.  The RFCs already in the index were tokenized with the previous
+    /// settings, so they can only be changed when building a new index.
+    pub fn set_tokenizer(&mut self, tokenizer: Tokenizer) -> RFSeeResult<()> {
+        if !self.processed_rfcs.is_empty() && self.index.tokenizer != tokenizer {
+            return Err(RFSeeError::RuntimeError(
+                "The tokenizer settings changed since the index was built, a full index is required"
+                    .to_string(),
+            ));
+        }
+        self.index.tokenizer = tokenizer;
+        Ok(())
+    }
+
+    /// Filter out the raw RFCs that are already in the index, so that only new ones are loaded.
+    /// The details of RFCs already in the index are refreshed from their raw entry.  RFC numbers
+    /// that were never issued, and entries that can't be parsed, are recorded in the report instead
+    /// of being loaded.
+    fn new_raw_rfcs<'a>(
+        &mut self,
+        raw_rfcs: Vec<&'a str>,
+        report: &mut LoadReport,
+    ) -> Vec<&'a str> {
+        let mut new_rfcs = Vec::new();
+        for raw_rfc in raw_rfcs {
+            match parse_rfc_citation(raw_rfc) {
+                Ok((rfc_num, details)) if details.not_issued => {
+                    report.not_issued.push(rfc_num);
+                    self.index.rfc_details.insert(rfc_num, details);
+                }
+                // RFCs that were previously not issued have no document in the index yet
+                Ok((rfc_num, details))
+                    if self
+                        .index
+                        .rfc_details
+                        .get(&rfc_num)
+                        .is_some_and(|d| !d.not_issued) =>
+                {
+                    self.index.rfc_details.insert(rfc_num, details);
+                }
+                Ok(_) => new_rfcs.push(raw_rfc),
+                Err(err) => report.malformed.push((raw_rfc.to_string(), err)),
+            }
+        }
+        new_rfcs
+    }
+
+    /// Load the RFCs which aren't already in the index
+    pub fn load_rfcs(&mut self) -> RFSeeResult<LoadReport> {
+        self.index.mirror = self.source.mirror();
+        let raw_rfc_index = self.source.rfc_index()?;
         let raw_rfcs = parse_rfc_index(&raw_rfc_index)?;
+        let mut report = LoadReport::default();
+        let raw_rfcs = self.new_raw_rfcs(raw_rfcs, &mut report);
         for raw_rfc in raw_rfcs {
-            let (rfc_num, title) = parse_rfc_details(raw_rfc)?;
-            let url = format!("{RFC_EDITOR_URL_BASE}{rfc_num}.txt");
-            let maybe_parsed_rfc = match fetch(&url) {
-                Ok(content) => Some(RfcEntry {
-                    number: rfc_num,
-                    url: url.clone(),
-                    title: title.replace("\n     ", " ").to_string(),
-                    content: Some(content),
-                }),
-                Err(_) => None,
+            let maybe_parsed_rfc = match self.source.rfc(raw_rfc) {
+                Ok(rfc) => Some(rfc),
+                Err(err) => {
+                    if let Ok((rfc_num, _)) = parse_rfc_details(raw_rfc) {
+                        report.record_error(rfc_num, err);
+                    }
+                    None
+                }
             };
             if let Some(parsed_rfc) = maybe_parsed_rfc {
                 if parsed_rfc.content.is_some() {
+                    report.indexed.push(parsed_rfc.number);
                     self.add_rfc_entry(parsed_rfc)
                 }
             }
         }
 
-        Ok(())
+        Ok(report)
     }
 
-    /// Load the RFCs in parallel using a threadpool
+    /// Load the RFCs which aren't already in the index in parallel using a threadpool.  RFCs that
+    /// can't be fetched, for example because the request timed out, are recorded in the returned
+    /// report and the load finishes with the RFCs that could be.
     pub fn par_load_rfcs(
         &mut self,
         progress_cb: extern "C" fn(progress: *const c_char),
-    ) -> RFSeeResult<()> {
+    ) -> RFSeeResult<LoadReport> {
         let pool = threadpool::ThreadPool::new(12);
-        let raw_rfc_index = fetch_rfc_index()?;
+        self.index.mirror = self.source.mirror();
+        let raw_rfc_index = self.source.rfc_index()?;
         let raw_rfcs = parse_rfc_index(&raw_rfc_index)?;
+        let mut report = LoadReport::default();
+        let raw_rfcs = self.new_raw_rfcs(raw_rfcs, &mut report);
+        if let Ok(msg) = CString::new(format!("{} new RFCs to fetch", raw_rfcs.len())) {
+            progress_cb(msg.as_ptr())
+        }
         let rfcs_count = raw_rfcs.len();
 
         let parsed_rfcs: Vec<RfcEntry> = Vec::new();
         let parsed_rfcs = Arc::new(Mutex::new(parsed_rfcs));
+        let report = Arc::new(Mutex::new(report));
 
         let remaining = raw_rfcs.len();
         let remaining = Arc::new(Mutex::new(remaining));
@@ -160,10 +440,20 @@ impl TfIdf {
             let string = rfc.to_string();
             let remaining = Arc::clone(&remaining);
             let parsed_rfcs = Arc::clone(&parsed_rfcs);
+            let report = Arc::clone(&report);
+            let source = self.source.clone();
             pool.execute(move || {
-                if let Ok(r) = fetch_rfc(&string) {
-                    let mut guard = parsed_rfcs.lock().unwrap();
-                    guard.push(r);
+                match source.rfc(&string) {
+                    Ok(r) => {
+                        let mut guard = parsed_rfcs.lock().unwrap();
+                        guard.push(r);
+                    }
+                    Err(err) => {
+                        if let Ok((rfc_num, _)) = parse_rfc_details(&string) {
+                            let mut guard = report.lock().unwrap();
+                            guard.record_error(rfc_num, err);
+                        }
+                    }
                 };
                 let mut guard = remaining.lock().unwrap();
                 *guard -= 1;
@@ -187,11 +477,30 @@ impl TfIdf {
                 std::thread::sleep(Duration::from_secs(5));
             }
         }
+        // Wait for the workers to release their references to the results
+        drop(pool);
+
+        let mut report = match Arc::try_unwrap(report) {
+            Ok(mutex) => match mutex.into_inner() {
+                Ok(mut report) => {
+                    report.skipped.sort();
+                    report.failed.sort_by_key(|(rfc_num, _)| *rfc_num);
+                    report
+                }
+                Err(err) => return Err(RFSeeError::RuntimeError(err.to_string())),
+            },
+            Err(_) => {
+                return Err(RFSeeError::RuntimeError(
+                    "More than one reference remaining".to_string(),
+                ))
+            }
+        };
 
         match Arc::try_unwrap(parsed_rfcs) {
             Ok(mutex) => match mutex.into_inner() {
                 Ok(rfcs) => {
                     for (i, rfc) in rfcs.into_iter().enumerate() {
+                        report.indexed.push(rfc.number);
                         self.add_rfc_entry(rfc);
                         if i % 100 == 0 {
                             let progress = (i as f64 / rfcs_count as f64) * 100_f64;
@@ -211,41 +520,40 @@ impl TfIdf {
                 ))
             }
         }
+        report.indexed.sort();
 
-        Ok(())
+        Ok(report)
     }
 
-    /// Process `RfcEntry` by computing it's term frequencies and add it to index
+    /// Process `RfcEntry` by computing it's term frequencies, without the page headers, footers and
+    /// boilerplate, and add it to index
     pub fn add_rfc_entry(&mut self, rfc: RfcEntry) {
-        let re = Regex::new(WORD_MATCH_REGEX).unwrap();
-
         if let Some(content) = &rfc.content {
-            let mut term_counts: HashMap<&str, usize> = HashMap::new();
+            let mut term_counts: HashMap<Term, usize> = HashMap::new();
             let mut tfs = TermFreqs::new();
             let mut terms = 0;
 
-            for found in re.find_iter(content) {
-                if let Some(k) = term_counts.get_mut(found.as_str()) {
+            for term in self.index.tokenizer.tokenize(&clean_rfc_text(content)) {
+                if let Some(k) = term_counts.get_mut(&term) {
                     *k += 1
                 } else {
-                    term_counts.insert(found.as_str(), 1);
+                    term_counts.insert(term, 1);
                 }
                 terms += 1
             }
 
             for (t, c) in term_counts {
                 let frequency = c as f32 / terms as f32;
-                tfs.insert(t.to_string(), frequency);
+                tfs.insert(t, frequency);
             }
 
             let indexed_rfc = ProcessedRfc {
                 number: rfc.number,
                 term_freqs: tfs,
+                length: terms,
             };
 
-            self.index
-                .rfc_details
-                .insert(rfc.number, RfcDetails { title: rfc.title });
+            self.index.rfc_details.insert(rfc.number, rfc.details);
 
             self.processed_rfcs.insert(rfc.url, indexed_rfc);
         }
@@ -254,6 +562,10 @@ impl TfIdf {
     /// Take all the processed documents and their term frequencies to compute the final term
     /// scores
     pub fn finish(&mut self, progress_cb: extern "C" fn(*const c_char)) {
+        // Scores depend on every document, so when the index is updated they are all recomputed
+        self.idfs.clear();
+        self.index.term_scores.clear();
+
         if let Ok(msg) = CString::new("Collecting terms") {
             progress_cb(msg.as_ptr())
         }
@@ -275,11 +587,23 @@ impl TfIdf {
         // Then we compute the inverse document frequency for each term
         let total_docs = self.processed_rfcs.len();
         for (term, docs_with_term) in term_counts {
-            let inv_fraction = (total_docs as f32) / ((docs_with_term as f32) + EPSILON);
-            let scaled = inv_fraction.log10();
-            self.idfs.insert(term.clone(), scaled);
+            let idf = match self.index.scoring {
+                ScoringModel::TfIdf => {
+                    let inv_fraction = (total_docs as f32) / ((docs_with_term as f32) + EPSILON);
+                    inv_fraction.log10()
+                }
+                // Adding 1 keeps the IDF of terms in more than half of the RFCs positive
+                ScoringModel::Bm25 { .. } => {
+                    let docs_with_term = docs_with_term as f32;
+                    ((total_docs as f32 - docs_with_term + 0.5) / (docs_with_term + 0.5)).ln_1p()
+                }
+            };
+            self.idfs.insert(term.clone(), idf);
         }
 
+        let total_length: usize = self.processed_rfcs.values().map(|rfc| rfc.length).sum();
+        let avg_length = total_length as f32 / total_docs.max(1) as f32;
+
         if let Ok(msg) = CString::new("Scoring documents") {
             progress_cb(msg.as_ptr())
         }
@@ -287,9 +611,21 @@ impl TfIdf {
         self.processed_rfcs.iter().for_each(|(_doc, rfc)| {
             for (doc_term, freq) in &rfc.term_freqs {
                 if let Some(idf) = self.idfs.get(doc_term) {
-                    // there are often lots of 0s preceding actual score, we can remove those and
-                    // convert to integer to save some space
-                    let doc_term_score = (freq * idf) * 1_000_000_000.0;
+                    let doc_term_score = match self.index.scoring {
+                        // there are often lots of 0s preceding actual score, we can remove those
+                        // and convert to integer to save some space
+                        ScoringModel::TfIdf => (freq * idf) * TF_IDF_SCORE_SCALE,
+                        ScoringModel::Bm25 { k1, b } => {
+                            let count = freq * rfc.length as f32;
+                            let length_norm = if avg_length > 0.0 {
+                                1.0 - b + b * rfc.length as f32 / avg_length
+                            } else {
+                                1.0
+                            };
+                            let saturated = count * (k1 + 1.0) / (count + k1 * length_norm);
+                            idf * saturated * BM25_SCORE_SCALE
+                        }
+                    };
                     let rounded_doc_term_score = doc_term_score.round() as i32;
                     if let Some(term_scores_per_doc) = self.index.term_scores.get_mut(doc_term) {
                         term_scores_per_doc.insert(rfc.number, rounded_doc_term_score);
@@ -305,23 +641,29 @@ impl TfIdf {
         });
     }
 
-    /// Save index to disk
-    pub fn save(&self, path: &Path) {
-        {
-            let index_file = std::fs::File::create(path).unwrap();
-            simd_json::to_writer(index_file, &self.index).unwrap();
-        }
+    /// Save index to disk, along with the per document state used to update it later
+    pub fn save(&self, path: &Path) -> RFSeeResult<()> {
+        let create = |path: &Path| {
+            File::create(path).map_err(|e| {
+                RFSeeError::IOError(format!("Unable to create {}: {e}", path.display()))
+            })
+        };
+        simd_json::to_writer(create(path)?, &self.index)
+            .map_err(|e| RFSeeError::IOError(e.to_string()))?;
+        simd_json::to_writer(create(&get_state_path(path))?, &self.processed_rfcs)
+            .map_err(|e| RFSeeError::IOError(e.to_string()))
     }
 }
 
 /// Combine the result set for each term into a single result set where a document only shows up
-/// once.  Scores are combined by adding them.
-pub fn combine_scores(scores: Vec<HashMap<i32, i32>>) -> Vec<i32> {
+/// once.  Scores are combined by adding them, saturating at `i32::MAX`, and the documents are
+/// returned with the highest scoring first.
+pub fn combine_scores(scores: Vec<HashMap<i32, i32>>) -> Vec<(RfcNumber, TermScore)> {
     let mut combined_scores: HashMap<i32, i32> = HashMap::new();
     for score in scores {
         for (rfc_num, term_score) in score {
             if let Some(combined_doc_score) = combined_scores.get_mut(&rfc_num) {
-                *combined_doc_score += term_score;
+                *combined_doc_score = combined_doc_score.saturating_add(term_score);
             } else {
                 combined_scores.insert(rfc_num, term_score);
             }
@@ -332,37 +674,148 @@ pub fn combine_scores(scores: Vec<HashMap<i32, i32>>) -> Vec<i32> {
 
     // Sort by score in descending order
     scores_list.sort_by(|(_, a_score), (_, b_score)| b_score.partial_cmp(a_score).unwrap());
+    scores_list
+}
 
-    // Return only the URLs
-    scores_list.into_iter().map(|(rfc, _)| rfc).collect()
+/// How search treats RFCs that were obsoleted by a later one
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ObsoletedRfcs {
+    /// Rank them like any other RFC
+    #[default]
+    Show,
+    /// Leave them out of the results
+    Hide,
+    /// Multiply their score by the factor, between 0 and 1, so they rank below current RFCs
+    DownRank(f32),
 }
 
-/// Search the provided index for the terms and return ordered results
-pub fn search_index(search: String, index: Index) -> Vec<RfcSearchResult> {
-    // Extract all the terms from the search
-    let terms: Vec<&str> = search.split(SEARCH_TERMS_DELIMITER).collect();
+/// Narrows search results down using the details from the RFC index.  Statuses and streams are
+/// matched ignoring case, with `-` and `_` treated as spaces, so `standards-track` matches every
+/// standards track status.  Empty and unset fields match every RFC.
+#[derive(Clone, Debug, Default)]
+pub struct SearchFilter {
+    /// Only include RFCs with one of these statuses, such as `informational`
+    pub statuses: Vec<String>,
+    /// Exclude RFCs with any of these statuses, such as `historic`
+    pub exclude_statuses: Vec<String>,
+    /// Only include RFCs from one of these streams, such as `ietf`
+    pub streams: Vec<String>,
+    /// Only include RFCs published in or after this year
+    pub from_year: Option<u16>,
+    /// Only include RFCs published in or before this year
+    pub to_year: Option<u16>,
+    /// Whether RFCs obsoleted by a later one are shown, hidden or ranked lower
+    pub obsoleted: ObsoletedRfcs,
+}
+
+impl SearchFilter {
+    fn normalize(value: &str) -> String {
+        value.trim().replace(['-', '_'], " ").to_uppercase()
+    }
+
+    fn status_matches(filter: &str, status: &str) -> bool {
+        let filter = Self::normalize(filter);
+        let status = Self::normalize(status);
+        filter == status
+            || (filter == STANDARDS_TRACK && STANDARDS_TRACK_STATUSES.contains(&&*status))
+    }
+
+    /// Check the filter can be used for a search, the obsoleted penalty must be above 0 and at
+    /// most 1
+    pub fn validate(&self) -> RFSeeResult<()> {
+        match self.obsoleted {
+            ObsoletedRfcs::DownRank(factor) if !(factor > 0.0 && factor <= 1.0) => {
+                Err(RFSeeError::ParseError(format!(
+                    "The obsoleted penalty must be above 0 and at most 1, not {factor}"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether an RFC with these details passes the filter
+    pub fn matches(&self, details: &RfcDetails) -> bool {
+        if self.obsoleted == ObsoletedRfcs::Hide && !details.obsoleted_by.is_empty() {
+            return false;
+        }
+        let status = details.status.as_deref().unwrap_or_default();
+        if !self.statuses.is_empty()
+            && !self
+                .statuses
+                .iter()
+                .any(|s| Self::status_matches(s, status))
+        {
+            return false;
+        }
+        if self
+            .exclude_statuses
+            .iter()
+            .any(|s| Self::status_matches(s, status))
+        {
+            return false;
+        }
+        if !self.streams.is_empty() {
+            let stream = Self::normalize(details.stream.as_deref().unwrap_or_default());
+            if !self.streams.iter().any(|s| Self::normalize(s) == stream) {
+                return false;
+            }
+        }
+        match details.year {
+            Some(year) => {
+                self.from_year.is_none_or(|from| year >= from)
+                    && self.to_year.is_none_or(|to| year <= to)
+            }
+            None => self.from_year.is_none() && self.to_year.is_none(),
+        }
+    }
+}
+
+/// Search the provided index for the terms and return ordered results that pass the filter
+pub fn search_index(search: String, index: Index, filter: &SearchFilter) -> Vec<RfcSearchResult> {
+    // Extract all the terms from the search, the same way they were extracted from the RFCs
+    let terms = index.tokenizer.tokenize(&search);
 
     // Extract the top documents for each term
     let mut scores = Vec::new();
     for term in terms {
-        if let Some(term_scores) = index.term_scores.get(term) {
+        if let Some(term_scores) = index.term_scores.get(&term) {
             scores.push(term_scores.clone());
         }
     }
 
     // Combine the scores by adding them for each document
-    let rfcs = combine_scores(scores);
+    let mut rfcs = combine_scores(scores);
+    if let ObsoletedRfcs::DownRank(factor) = filter.obsoleted {
+        for (n, score) in rfcs.iter_mut() {
+            if index
+                .rfc_details
+                .get(n)
+                .is_some_and(|d| !d.obsoleted_by.is_empty())
+            {
+                *score = (*score as f32 * factor) as TermScore;
+            }
+        }
+        // The sort is stable so equal scores keep their order
+        rfcs.sort_by(|(_, a_score), (_, b_score)| b_score.cmp(a_score));
+    }
+    let url_base = &index.mirror.result_url_base;
     rfcs.iter()
-        .map(|n| {
+        .filter(|(n, _)| match index.rfc_details.get(n) {
+            Some(details) => filter.matches(details),
+            None => filter.matches(&RfcDetails::default()),
+        })
+        .map(|(n, _)| {
             if let Some(details) = index.rfc_details.get(n) {
                 RfcSearchResult {
-                    url: format!("{RFC_EDITOR_URL_BASE}{n}.{RFC_EDITOR_FILE_TYPE}"),
+                    url: format!("{url_base}{n}.{RFC_EDITOR_FILE_TYPE}"),
                     title: details.title.clone(),
+                    obsoleted_by: details.obsoleted_by.clone(),
                 }
             } else {
                 RfcSearchResult {
-                    url: format!("{RFC_EDITOR_URL_BASE}{n}.{RFC_EDITOR_FILE_TYPE}"),
+                    url: format!("{url_base}{n}.{RFC_EDITOR_FILE_TYPE}"),
                     title: "MISSING TITLE".to_string(),
+                    obsoleted_by: Vec::new(),
                 }
             }
         })
@@ -371,9 +824,22 @@ pub fn search_index(search: String, index: Index) -> Vec<RfcSearchResult> {
 
 #[cfg(test)]
 mod tests {
-    use std::ffi::c_char;
+    use std::{
+        collections::HashMap,
+        ffi::c_char,
+        io::{BufRead, BufReader, Write},
+        path::PathBuf,
+        time::Duration,
+    };
 
-    use super::{parse_rfc_index, RfcEntry, TfIdf};
+    use super::{
+        combine_scores, get_state_path, parse_rfc_index, search_index, LoadReport, ObsoletedRfcs,
+        RfcDetails, RfcEntry, RfcSource, ScoringModel, SearchFilter, TfIdf, DEFAULT_BM25_B,
+        DEFAULT_BM25_K1,
+    };
+    use crate::error::RFSeeError;
+    use crate::fetch::{Client, FetchConfig, Mirror, RetryPolicy};
+    use crate::tokenize::Tokenizer;
 
     extern "C" fn dummy_cb(_msg: *const c_char) {}
 
@@ -382,7 +848,7 @@ mod tests {
         let index_contents = std::fs::read_to_string("../../data/rfc_index.txt").unwrap();
         let parsed = parse_rfc_index(&index_contents).unwrap();
 
-        assert_eq!(parsed, vec!["0001 Host Software. S. Crocker. April 1969. (Format: TXT, HTML) (Status:\n     UNKNOWN) (DOI: 10.17487/RFC0001) ", "0002 Host software. B. Duvall. April 1969. (Format: TXT, PDF, HTML)\n     (Status: UNKNOWN) (DOI: 10.17487/RFC0002) ", ""]);
+        assert_eq!(parsed, vec!["0001 Host Software. S. Crocker. April 1969. (Format: TXT, HTML) (Status:\n     UNKNOWN) (DOI: 10.17487/RFC0001)", "0002 Host software. B. Duvall. April 1969. (Format: TXT, PDF, HTML)\n     (Status: UNKNOWN) (DOI: 10.17487/RFC0002)"]);
     }
 
     #[test]
@@ -390,7 +856,10 @@ mod tests {
         let mut tf_idf = TfIdf::default();
         let entry = RfcEntry {
             content: Some("Hello world!".to_string()),
-            title: "Test".to_string(),
+            details: RfcDetails {
+                title: "Test".to_string(),
+                ..Default::default()
+            },
             number: 1,
             url: "https://www.rfsee.com/1".to_string(),
         };
@@ -400,7 +869,7 @@ mod tests {
         assert_eq!(tf_idf.index.rfc_details.len(), 1);
         assert_eq!(tf_idf.index.term_scores.len(), 2);
 
-        let hello = tf_idf.index.term_scores.get("Hello");
+        let hello = tf_idf.index.term_scores.get("hello");
         assert!(hello.is_some());
         let hello_doc_score = hello.unwrap().get(&1);
         assert!(hello_doc_score.is_some());
@@ -411,7 +880,10 @@ mod tests {
         let mut tf_idf = TfIdf::default();
         let entry = RfcEntry {
             content: Some("Hello hello!".to_string()),
-            title: "Test".to_string(),
+            details: RfcDetails {
+                title: "Test".to_string(),
+                ..Default::default()
+            },
             number: 1,
             url: "https://www.rfsee.com/1".to_string(),
         };
@@ -419,10 +891,432 @@ mod tests {
         tf_idf.finish(dummy_cb);
 
         assert_eq!(tf_idf.index.rfc_details.len(), 1);
-        // This should be 1 once we update parsing to treat "Hello" and "hello" the same
-        assert_eq!(tf_idf.index.term_scores.len(), 2);
+        assert_eq!(tf_idf.index.term_scores.len(), 1);
 
-        let hello = tf_idf.index.term_scores.get("Hello");
+        let hello = tf_idf.index.term_scores.get("hello");
         assert!(hello.is_some());
     }
+
+    #[test]
+    fn test_stopwords_are_not_indexed() {
+        let mut tf_idf = TfIdf::default();
+        tf_idf.add_rfc_entry(RfcEntry {
+            content: Some("The TLS handshake".to_string()),
+            details: RfcDetails {
+                title: "Test".to_string(),
+                ..Default::default()
+            },
+            number: 1,
+            url: "https://www.rfsee.com/1".to_string(),
+        });
+        tf_idf.finish(dummy_cb);
+
+        assert!(!tf_idf.index.term_scores.contains_key("the"));
+        let results = search_index(
+            "the TLS".to_string(),
+            tf_idf.index.clone(),
+            &SearchFilter::default(),
+        );
+        assert_eq!(results.len(), 1);
+
+        // RFCs already in the index were tokenized with the old stopwords
+        let mut tokenizer = Tokenizer::default();
+        tokenizer.stopwords.custom.insert("tls".to_string());
+        assert!(tf_idf.set_tokenizer(tokenizer).is_err());
+        assert!(tf_idf.set_tokenizer(Tokenizer::default()).is_ok());
+    }
+
+    #[test]
+    fn test_load_rfcs_from_local_dir() {
+        let mut tf_idf = TfIdf {
+            source: RfcSource::LocalDir(PathBuf::from("../../data/mirror")),
+            ..Default::default()
+        };
+        let report = tf_idf.par_load_rfcs(dummy_cb).unwrap();
+        tf_idf.finish(dummy_cb);
+
+        assert_eq!(report.indexed, vec![1, 2]);
+        assert!(report.skipped.is_empty());
+        assert_eq!(tf_idf.index.rfc_details.len(), 2);
+        let imp = tf_idf.index.term_scores.get("imp");
+        assert!(imp.is_some_and(|docs| docs.contains_key(&2)));
+    }
+
+    #[test]
+    fn test_par_load_rfcs_keeps_partial_results() {
+        // Serve the local mirror over HTTP, except that requests for RFC 2 are never answered
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let mut stalled = Vec::new();
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut req = String::new();
+                while !req.ends_with("\r\n\r\n") {
+                    reader.read_line(&mut req).unwrap();
+                }
+                let path = req.split(' ').nth(1).unwrap_or_default().to_string();
+                if path == "/rfc2.txt" {
+                    stalled.push(reader);
+                    continue;
+                }
+                let body = std::fs::read_to_string(format!("../../data/mirror{path}")).unwrap();
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+        let client = Client::new(FetchConfig {
+            read_timeout: Duration::from_millis(200),
+            retry: RetryPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mirror = Mirror {
+            index_url: format!("http://127.0.0.1:{port}/rfc-index.txt"),
+            rfc_url_base: format!("http://127.0.0.1:{port}/rfc"),
+            ..Default::default()
+        };
+        let mut tf_idf = TfIdf {
+            source: RfcSource::Remote(client, mirror, None),
+            ..Default::default()
+        };
+        let report = tf_idf.par_load_rfcs(dummy_cb).unwrap();
+
+        assert_eq!(report.indexed, vec![1]);
+        assert!(matches!(report.failed[..], [(2, RFSeeError::Timeout(_))]));
+        assert_eq!(tf_idf.index.rfc_details.len(), 1);
+    }
+
+    #[test]
+    fn test_save_to_missing_dir_fails() {
+        let path = std::env::temp_dir().join("rfsee_test_missing_dir/index.json");
+        assert!(matches!(
+            TfIdf::default().save(&path),
+            Err(RFSeeError::IOError(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_and_load_for_update() {
+        let mut tf_idf = TfIdf {
+            source: RfcSource::LocalDir(PathBuf::from("../../data/mirror")),
+            ..Default::default()
+        };
+        tf_idf.par_load_rfcs(dummy_cb).unwrap();
+        tf_idf.finish(dummy_cb);
+        let path = std::env::temp_dir().join("rfsee_test_save_and_load_for_update.json");
+        tf_idf.save(&path).unwrap();
+
+        let loaded = TfIdf::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(get_state_path(&path)).unwrap();
+        let mut loaded = loaded.unwrap();
+        assert_eq!(loaded.processed_rfcs.len(), 2);
+        assert_eq!(loaded.index.mirror, tf_idf.index.mirror);
+        loaded.finish(dummy_cb);
+        assert_eq!(loaded.index.term_scores, tf_idf.index.term_scores);
+    }
+
+    #[test]
+    fn test_only_new_rfcs_are_loaded() {
+        let index_contents = std::fs::read_to_string("../../data/rfc_index.txt").unwrap();
+        let parsed = parse_rfc_index(&index_contents).unwrap();
+        let mut tf_idf = TfIdf::default();
+        tf_idf.index.rfc_details.insert(
+            1,
+            RfcDetails {
+                title: "Old title".to_string(),
+                ..Default::default()
+            },
+        );
+
+        let new_rfcs = tf_idf.new_raw_rfcs(parsed, &mut LoadReport::default());
+        assert_eq!(new_rfcs.len(), 1);
+        assert!(new_rfcs[0].starts_with("0002"));
+        assert_eq!(tf_idf.index.rfc_details[&1].title, "Host Software");
+        assert_eq!(tf_idf.index.rfc_details[&1].authors, vec!["S. Crocker"]);
+    }
+
+    #[test]
+    fn test_search_results_use_mirror_url_base() {
+        let mut tf_idf = TfIdf::default();
+        tf_idf.add_rfc_entry(RfcEntry {
+            content: Some("Hello world!".to_string()),
+            details: RfcDetails {
+                title: "Test".to_string(),
+                ..Default::default()
+            },
+            number: 1,
+            url: "http://localhost:8080/rfc1.txt".to_string(),
+        });
+        tf_idf.finish(dummy_cb);
+        let mut index = tf_idf.index;
+        index.mirror.result_url_base = "http://localhost:8080/rfc".to_string();
+
+        let results = search_index("Hello".to_string(), index, &SearchFilter::default());
+        assert_eq!(results[0].url, "http://localhost:8080/rfc1.txt");
+    }
+
+    #[test]
+    fn test_search_obsoleted_rfcs() {
+        let mut tf_idf = TfIdf::default();
+        let docs = [
+            (2616, "HTTP HTTP HTTP caching", vec![9110]),
+            (9110, "HTTP semantics", vec![]),
+            (1, "Host software", vec![]),
+        ];
+        for (number, content, obsoleted_by) in docs {
+            tf_idf.add_rfc_entry(RfcEntry {
+                content: Some(content.to_string()),
+                details: RfcDetails {
+                    title: format!("RFC {number}"),
+                    obsoleted_by,
+                    ..Default::default()
+                },
+                number,
+                url: format!("https://www.rfc-editor.org/rfc/rfc{number}.txt"),
+            });
+        }
+        tf_idf.finish(dummy_cb);
+        let index = tf_idf.index;
+
+        let results = search_index("HTTP".to_string(), index.clone(), &SearchFilter::default());
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "RFC 2616");
+        assert_eq!(
+            results[0].annotation().as_deref(),
+            Some("obsoleted by RFC 9110")
+        );
+        assert_eq!(results[1].annotation(), None);
+
+        let down_ranked = SearchFilter {
+            obsoleted: ObsoletedRfcs::DownRank(0.1),
+            ..Default::default()
+        };
+        let results = search_index("HTTP".to_string(), index.clone(), &down_ranked);
+        assert_eq!(results[0].title, "RFC 9110");
+        assert_eq!(results[1].title, "RFC 2616");
+
+        let hidden = SearchFilter {
+            obsoleted: ObsoletedRfcs::Hide,
+            ..Default::default()
+        };
+        let results = search_index("HTTP".to_string(), index, &hidden);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "RFC 9110");
+    }
+
+    #[test]
+    fn test_search_filter_validate() {
+        for factor in [0.1, 1.0] {
+            let filter = SearchFilter {
+                obsoleted: ObsoletedRfcs::DownRank(factor),
+                ..Default::default()
+            };
+            assert!(filter.validate().is_ok());
+        }
+        for factor in [0.0, -0.5, 1.5, f32::NAN] {
+            let filter = SearchFilter {
+                obsoleted: ObsoletedRfcs::DownRank(factor),
+                ..Default::default()
+            };
+            assert!(matches!(filter.validate(), Err(RFSeeError::ParseError(_))));
+        }
+    }
+
+    #[test]
+    fn test_combine_scores_saturates() {
+        let scores = vec![
+            HashMap::from([(1, i32::MAX - 10), (2, 5)]),
+            HashMap::from([(1, 20), (2, 5)]),
+        ];
+        assert_eq!(combine_scores(scores), vec![(1, i32::MAX), (2, 10)]);
+    }
+
+    #[test]
+    fn test_scoring_model_validate() {
+        assert!(ScoringModel::TfIdf.validate().is_ok());
+        assert!(ScoringModel::bm25().validate().is_ok());
+        assert!(ScoringModel::Bm25 { k1: 0.0, b: 0.0 }.validate().is_ok());
+        assert!(ScoringModel::Bm25 { k1: 2.0, b: 1.0 }.validate().is_ok());
+        assert!(ScoringModel::Bm25 { k1: 10.0, b: 1.0 }.validate().is_ok());
+        for (k1, b) in [
+            (-0.1, 0.75),
+            (1000.0, 0.75),
+            (1.2, -0.1),
+            (1.2, 1.5),
+            (f32::NAN, 0.75),
+        ] {
+            assert!(matches!(
+                ScoringModel::Bm25 { k1, b }.validate(),
+                Err(RFSeeError::ParseError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_bm25_saturates_term_frequency() {
+        let filler = |n: usize| (0..n).map(|i| format!("word{i} ")).collect::<String>();
+        let docs = [
+            (1, "tls ".repeat(10)),
+            (2, format!("tls {}", filler(9))),
+            (3, filler(10)),
+        ];
+        let scores = |scoring| {
+            let mut tf_idf = TfIdf::default();
+            tf_idf.index.scoring = scoring;
+            for (number, content) in &docs {
+                tf_idf.add_rfc_entry(RfcEntry {
+                    content: Some(content.clone()),
+                    details: RfcDetails::default(),
+                    number: *number,
+                    url: format!("https://www.rfsee.com/{number}"),
+                });
+            }
+            tf_idf.finish(dummy_cb);
+            let tls = &tf_idf.index.term_scores["tls"];
+            (tls[&1] as f32, tls[&2] as f32)
+        };
+
+        let (repeated, once) = scores(ScoringModel::TfIdf);
+        assert!((repeated / once - 10.0).abs() < 0.01);
+        // Repeating a term can't add more than `k1 + 1` times the score of a single occurrence
+        let (repeated, once) = scores(ScoringModel::bm25());
+        assert!(repeated > once);
+        assert!(repeated / once < DEFAULT_BM25_K1 + 1.0);
+    }
+
+    #[test]
+    fn test_bm25_normalizes_document_length() {
+        let docs = [
+            (1, "tls handshake"),
+            (2, "tls handshake record layer alert"),
+            (3, "quic"),
+        ];
+        let scores = |b| {
+            let mut tf_idf = TfIdf::default();
+            tf_idf.index.scoring = ScoringModel::Bm25 {
+                k1: DEFAULT_BM25_K1,
+                b,
+            };
+            for (number, content) in docs {
+                tf_idf.add_rfc_entry(RfcEntry {
+                    content: Some(content.to_string()),
+                    details: RfcDetails::default(),
+                    number,
+                    url: format!("https://www.rfsee.com/{number}"),
+                });
+            }
+            tf_idf.finish(dummy_cb);
+            let tls = &tf_idf.index.term_scores["tls"];
+            (tls[&1], tls[&2])
+        };
+
+        let (short, long) = scores(0.0);
+        assert_eq!(short, long);
+        let (short, long) = scores(DEFAULT_BM25_B);
+        assert!(short > long);
+    }
+
+    #[test]
+    fn test_search_filter() {
+        let mut tf_idf = TfIdf::default();
+        tf_idf.add_rfc_entry(RfcEntry {
+            content: Some("Hello world!".to_string()),
+            details: RfcDetails {
+                title: "Test".to_string(),
+                status: Some("PROPOSED STANDARD".to_string()),
+                stream: Some("IETF".to_string()),
+                year: Some(2018),
+                ..Default::default()
+            },
+            number: 1,
+            url: "http://localhost:8080/rfc1.txt".to_string(),
+        });
+        tf_idf.finish(dummy_cb);
+        let details = &tf_idf.index.rfc_details[&1];
+
+        let standards_track = SearchFilter {
+            statuses: vec!["standards-track".to_string()],
+            streams: vec!["ietf".to_string()],
+            from_year: Some(2010),
+            ..Default::default()
+        };
+        assert!(standards_track.matches(details));
+        let informational = SearchFilter {
+            statuses: vec!["Informational".to_string()],
+            ..Default::default()
+        };
+        assert!(!informational.matches(details));
+        let not_proposed = SearchFilter {
+            exclude_statuses: vec!["proposed_standard".to_string()],
+            ..Default::default()
+        };
+        assert!(!not_proposed.matches(details));
+        let before_2010 = SearchFilter {
+            to_year: Some(2010),
+            ..Default::default()
+        };
+        assert!(!before_2010.matches(details));
+        assert!(!before_2010.matches(&RfcDetails::default()));
+
+        let index = tf_idf.index;
+        assert_eq!(
+            search_index("Hello".to_string(), index.clone(), &standards_track).len(),
+            1
+        );
+        assert!(search_index("Hello".to_string(), index, &informational).is_empty());
+    }
+
+    #[test]
+    fn test_not_issued_rfcs_are_not_loaded() {
+        let mut tf_idf = TfIdf::default();
+        let mut report = LoadReport::default();
+        let raw_rfcs = vec![
+            "0001 Host Software. S. Crocker. April 1969. (Format: TXT, HTML)",
+            "0003 Not Issued. ",
+        ];
+        let new_rfcs = tf_idf.new_raw_rfcs(raw_rfcs, &mut report);
+        assert_eq!(new_rfcs.len(), 1);
+        assert_eq!(report.not_issued, vec![3]);
+        assert!(tf_idf.index.rfc_details[&3].not_issued);
+
+        // An RFC number that is issued after all is loaded
+        let raw_rfcs = vec!["0003 Documentation conventions. S.D. Crocker. April 1969."];
+        let new_rfcs = tf_idf.new_raw_rfcs(raw_rfcs, &mut LoadReport::default());
+        assert_eq!(new_rfcs.len(), 1);
+    }
+
+    #[test]
+    fn test_malformed_rfcs_are_reported() {
+        let mut tf_idf = TfIdf::default();
+        let mut report = LoadReport::default();
+        let raw_rfcs = vec![
+            "0001 Host Software. S. Crocker. April 1969. (Format: TXT, HTML)",
+            "00x2 Host software. B. Duvall. April 1969.",
+        ];
+        let new_rfcs = tf_idf.new_raw_rfcs(raw_rfcs, &mut report);
+        assert_eq!(new_rfcs.len(), 1);
+        assert_eq!(report.malformed.len(), 1);
+        assert!(report.malformed[0].0.starts_with("00x2"));
+    }
+
+    #[test]
+    fn test_load_report_records_errors() {
+        let mut report = LoadReport::default();
+        let url = "https://www.rfsee.com/1".to_string();
+        report.record_error(1, RFSeeError::HttpStatusError(404, url.clone()));
+        report.record_error(2, RFSeeError::HttpStatusError(503, url));
+        report.record_error(3, RFSeeError::Timeout("Read timed out".to_string()));
+
+        assert_eq!(report.skipped, vec![(1, 404)]);
+        let failed: Vec<i32> = report.failed.iter().map(|(n, _)| *n).collect();
+        assert_eq!(failed, vec![2, 3]);
+    }
 }
@@ -0,0 +1,409 @@
+//! Decompression of DEFLATE (RFC 1951) data and the zlib (RFC 1950) and gzip (RFC 1952) formats
+//! wrapping it, as used by the `deflate` and `gzip` content encodings.
+
+use crate::error::{RFSeeError, RFSeeResult};
+
+/// Longest Huffman code allowed by DEFLATE
+const MAX_CODE_BITS: usize = 15;
+/// Base lengths for the length symbols 257..285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+/// Extra bits read after each length symbol, added to its base
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances for the distance symbols 0..29
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+/// Extra bits read after each distance symbol, added to its base
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order the code length code lengths are sent in for a dynamic block
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const GZIP_FLAG_HCRC: u8 = 0x02;
+const GZIP_FLAG_EXTRA: u8 = 0x04;
+const GZIP_FLAG_NAME: u8 = 0x08;
+const GZIP_FLAG_COMMENT: u8 = 0x10;
+/// Compression method for DEFLATE in the zlib and gzip headers
+const METHOD_DEFLATE: u8 = 8;
+
+fn corrupt(msg: &str) -> RFSeeError {
+    RFSeeError::ParseError(format!("Invalid compressed data: {msg}"))
+}
+
+/// Reads the bits of a DEFLATE stream, least significant bit first
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Index of the next byte to load
+    pos: usize,
+    buffer: u32,
+    buffered: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            buffer: 0,
+            buffered: 0,
+        }
+    }
+
+    fn bits(&mut self, n: u32) -> RFSeeResult<u32> {
+        while self.buffered < n {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or_else(|| corrupt("unexpected end of data"))?;
+            self.buffer |= (byte as u32) << self.buffered;
+            self.buffered += 8;
+            self.pos += 1;
+        }
+        let value = self.buffer & ((1_u32 << n) - 1);
+        self.buffer = self.buffer.checked_shr(n).unwrap_or(0);
+        self.buffered -= n;
+        Ok(value)
+    }
+
+    /// Drop the bits left in the current byte
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.buffered = 0;
+    }
+
+    /// Number of whole bytes consumed
+    fn consumed(&self) -> usize {
+        self.pos
+    }
+}
+
+/// A canonical Huffman code, stored as the number of codes of each length and the symbols
+/// ordered by their code
+struct Huffman {
+    counts: [u16; MAX_CODE_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Build the code from the code length of each symbol, where 0 means the symbol is unused
+    fn new(lengths: &[u8]) -> RFSeeResult<Self> {
+        let mut counts = [0_u16; MAX_CODE_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // Reject codes that have more codes of a length than there are left
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(corrupt("over-subscribed Huffman code"));
+            }
+        }
+
+        let mut offsets = [0_u16; MAX_CODE_BITS + 2];
+        for len in 1..=MAX_CODE_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0_u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(Self { counts, symbols })
+    }
+
+    /// Decode the next symbol, reading the code one bit at a time
+    fn decode(&self, reader: &mut BitReader) -> RFSeeResult<u16> {
+        // The first code of the current length and the index of its symbol
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_CODE_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(corrupt("invalid Huffman code"))
+    }
+}
+
+/// The fixed codes for literals and lengths, and for distances, used by block type 1
+fn fixed_codes() -> RFSeeResult<(Huffman, Huffman)> {
+    let mut lengths = [0_u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+/// Read the codes for a dynamic block, type 2, which are themselves Huffman coded
+fn dynamic_codes(reader: &mut BitReader) -> RFSeeResult<(Huffman, Huffman)> {
+    let literals = reader.bits(5)? as usize + 257;
+    let distances = reader.bits(5)? as usize + 1;
+    let code_lengths = reader.bits(4)? as usize + 4;
+    if literals > 286 || distances > 30 {
+        return Err(corrupt("too many codes"));
+    }
+
+    let mut lengths = [0_u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_lengths] {
+        lengths[symbol] = reader.bits(3)? as u8;
+    }
+    let length_code = Huffman::new(&lengths)?;
+
+    let mut lengths = vec![0_u8; literals + distances];
+    let mut i = 0;
+    while i < lengths.len() {
+        let symbol = length_code.decode(reader)?;
+        let (len, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths[..i]
+                    .last()
+                    .ok_or_else(|| corrupt("repeat with no previous length"))?;
+                (previous, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if i + repeat > lengths.len() {
+            return Err(corrupt("too many code lengths"));
+        }
+        lengths[i..i + repeat].fill(len);
+        i += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err(corrupt("missing end of block code"));
+    }
+    Ok((
+        Huffman::new(&lengths[..literals])?,
+        Huffman::new(&lengths[literals..])?,
+    ))
+}
+
+/// Decode the symbols of a Huffman coded block up to its end of block symbol
+fn inflate_block(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    literal_code: &Huffman,
+    distance_code: &Huffman,
+) -> RFSeeResult<()> {
+    loop {
+        let symbol = literal_code.decode(reader)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let idx = symbol - 257;
+                let len =
+                    LENGTH_BASE[idx] as usize + reader.bits(LENGTH_EXTRA[idx] as u32)? as usize;
+                let idx = distance_code.decode(reader)? as usize;
+                if idx >= DIST_BASE.len() {
+                    return Err(corrupt("invalid distance symbol"));
+                }
+                let dist = DIST_BASE[idx] as usize + reader.bits(DIST_EXTRA[idx] as u32)? as usize;
+                if dist > out.len() {
+                    return Err(corrupt("distance too far back"));
+                }
+                // The copy may overlap the bytes it produces, so it is done a byte at a time
+                let start = out.len() - dist;
+                for i in 0..len {
+                    out.push(out[start + i]);
+                }
+            }
+            _ => return Err(corrupt("invalid literal or length symbol")),
+        }
+    }
+}
+
+/// Decompress a raw DEFLATE stream, returning the data and the number of bytes of the input it
+/// took up
+fn inflate_with_len(data: &[u8]) -> RFSeeResult<(Vec<u8>, usize)> {
+    let mut reader = BitReader::new(data);
+    let mut out = Vec::with_capacity(data.len() * 4);
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let pos = reader.consumed();
+                let header = data
+                    .get(pos..pos + 4)
+                    .ok_or_else(|| corrupt("unexpected end of data"))?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                let nlen = u16::from_le_bytes([header[2], header[3]]);
+                if len != !nlen {
+                    return Err(corrupt("stored block length mismatch"));
+                }
+                let stored = data
+                    .get(pos + 4..pos + 4 + len as usize)
+                    .ok_or_else(|| corrupt("unexpected end of data"))?;
+                out.extend_from_slice(stored);
+                reader.pos = pos + 4 + len as usize;
+            }
+            1 => {
+                let (literal_code, distance_code) = fixed_codes()?;
+                inflate_block(&mut reader, &mut out, &literal_code, &distance_code)?;
+            }
+            2 => {
+                let (literal_code, distance_code) = dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut out, &literal_code, &distance_code)?;
+            }
+            _ => return Err(corrupt("invalid block type")),
+        }
+        if last {
+            return Ok((out, reader.consumed()));
+        }
+    }
+}
+
+/// Decompress a raw DEFLATE stream
+pub fn inflate(data: &[u8]) -> RFSeeResult<Vec<u8>> {
+    inflate_with_len(data).map(|(out, _)| out)
+}
+
+/// Decompress zlib wrapped DEFLATE data, checking its Adler-32 checksum
+pub fn zlib_decompress(data: &[u8]) -> RFSeeResult<Vec<u8>> {
+    let [cmf, flg, ..] = *data else {
+        return Err(corrupt("missing zlib header"));
+    };
+    if cmf & 0x0f != METHOD_DEFLATE || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 {
+        return Err(corrupt("invalid zlib header"));
+    }
+    if flg & 0x20 != 0 {
+        return Err(corrupt("zlib preset dictionaries are not supported"));
+    }
+    let (out, len) = inflate_with_len(&data[2..])?;
+    let checksum = data
+        .get(2 + len..2 + len + 4)
+        .ok_or_else(|| corrupt("missing zlib checksum"))?;
+    if u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) != adler32(&out) {
+        return Err(corrupt("zlib checksum mismatch"));
+    }
+    Ok(out)
+}
+
+/// Decompress gzip data, checking its CRC-32
+pub fn gunzip(data: &[u8]) -> RFSeeResult<Vec<u8>> {
+    if data.len() < 10 || data[..2] != GZIP_MAGIC || data[2] != METHOD_DEFLATE {
+        return Err(corrupt("invalid gzip header"));
+    }
+    let flags = data[3];
+    // Skip the modification time, extra flags and OS
+    let mut pos = 10;
+    if flags & GZIP_FLAG_EXTRA != 0 {
+        let len = data
+            .get(pos..pos + 2)
+            .ok_or_else(|| corrupt("invalid gzip header"))?;
+        pos += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [GZIP_FLAG_NAME, GZIP_FLAG_COMMENT] {
+        if flags & flag != 0 {
+            // Nul terminated strings
+            let len = data
+                .get(pos..)
+                .and_then(|rest| rest.iter().position(|&b| b == 0))
+                .ok_or_else(|| corrupt("invalid gzip header"))?;
+            pos += len + 1;
+        }
+    }
+    if flags & GZIP_FLAG_HCRC != 0 {
+        pos += 2;
+    }
+
+    let body = data
+        .get(pos..)
+        .ok_or_else(|| corrupt("invalid gzip header"))?;
+    let (out, len) = inflate_with_len(body)?;
+    let trailer = body
+        .get(len..len + 8)
+        .ok_or_else(|| corrupt("missing gzip trailer"))?;
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc != crc32(&out) || size != out.len() as u32 {
+        return Err(corrupt("gzip checksum mismatch"));
+    }
+    Ok(out)
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for &byte in data {
+        a = (a + byte as u32) % MOD;
+        b = (b + a) % MOD;
+    }
+    b << 16 | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                crc >> 1 ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gunzip, inflate, zlib_decompress};
+
+    const TEXT: &[u8] = b"Network Working Group. Request for Comments. Network Working Group.";
+
+    #[test]
+    fn test_inflate_stored_and_fixed_blocks() {
+        // A stored block with "abc" followed by a final fixed Huffman block with "abc" five times,
+        // the last four as a back reference
+        let data = [
+            0x00, 0x03, 0x00, 0xfc, 0xff, b'a', b'b', b'c', 0x4b, 0x4c, 0x4a, 0x4e, 0x44, 0x42,
+            0x00,
+        ];
+        assert_eq!(inflate(&data).unwrap(), b"abcabcabcabcabcabc");
+        assert!(inflate(&data[..6]).is_err());
+    }
+
+    #[test]
+    fn test_zlib_decompress() {
+        let data = include_bytes!("../../../data/compressed/text.zlib");
+        assert_eq!(zlib_decompress(data).unwrap(), TEXT);
+        let mut corrupted = data.to_vec();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xff;
+        assert!(zlib_decompress(&corrupted).is_err());
+    }
+
+    #[test]
+    fn test_gunzip_dynamic_block() {
+        let data = include_bytes!("../../../data/compressed/rfc1.txt.gz");
+        let expected = std::fs::read("../../data/mirror/rfc1.txt").unwrap();
+        assert_eq!(gunzip(data).unwrap(), expected);
+        assert!(gunzip(&data[..data.len() - 4]).is_err());
+    }
+}
@@ -1,8 +1,20 @@
+pub mod cache;
+pub mod config;
 pub mod error;
-mod fetch;
+pub mod fetch;
 mod index;
+mod inflate;
+mod mirror;
+mod normalize;
 mod parse;
 mod path;
+mod proxy;
+mod rate_limit;
+mod stem;
+pub mod stopwords;
 mod threadpool;
+pub mod tokenize;
+mod unicode_tables;
+mod url;
 
 pub use index::*;
@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use crate::{
+    error::{RFSeeError, RFSeeResult},
+    parse::parse_rfc_citation,
+    RfcEntry,
+};
+
+/// Name of the RFC index file at the root of an RFC Editor mirror
+const RFC_INDEX_FILE_NAME: &str = "rfc-index.txt";
+
+/// Return the raw `String` contents of the RFC index in a local RFC Editor mirror
+pub fn read_rfc_index(dir: &Path) -> RFSeeResult<String> {
+    let path = dir.join(RFC_INDEX_FILE_NAME);
+    std::fs::read_to_string(&path)
+        .map_err(|e| RFSeeError::IOError(format!("{}: {e}", path.display())))
+}
+
+/// Read the document for a raw RFC index entry from a local RFC Editor mirror, where each RFC is
+/// stored as `rfcNNNN.txt`
+pub fn read_rfc(dir: &Path, raw_rfc: &str) -> RFSeeResult<RfcEntry> {
+    let (rfc_num, details) = parse_rfc_citation(raw_rfc)?;
+    let path = dir.join(format!("rfc{rfc_num}.txt"));
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| RFSeeError::IOError(format!("{}: {e}", path.display())))?;
+    Ok(RfcEntry {
+        number: rfc_num,
+        url: path.display().to_string(),
+        details,
+        content: Some(content),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{read_rfc, read_rfc_index};
+    use crate::parse::parse_rfc_index;
+
+    const MIRROR_DIR: &str = "../../data/mirror";
+
+    #[test]
+    fn test_read_rfc_index() {
+        let contents = read_rfc_index(Path::new(MIRROR_DIR)).unwrap();
+        let parsed = parse_rfc_index(&contents).unwrap();
+        assert_eq!(parsed.len(), 2);
+    }
+
+    #[test]
+    fn test_read_rfc() {
+        let contents = read_rfc_index(Path::new(MIRROR_DIR)).unwrap();
+        let parsed = parse_rfc_index(&contents).unwrap();
+        let rfc = read_rfc(Path::new(MIRROR_DIR), parsed[0]).unwrap();
+        assert_eq!(rfc.number, 1);
+        assert_eq!(rfc.details.title, "Host Software");
+        assert!(rfc.content.unwrap().contains("ARPA Network"));
+    }
+
+    #[test]
+    fn test_read_missing_rfc() {
+        let res = read_rfc(Path::new(MIRROR_DIR), "9999 Missing. (Status: UNKNOWN)");
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::unicode_tables::{COMBINING_CLASSES, COMPOSITIONS, DECOMPOSITIONS};
+
+/// Hangul syllables are made of a leading consonant, a vowel and an optional trailing consonant
+/// jamo, so they are decomposed and composed arithmetically instead of with the tables
+const HANGUL_S_BASE: u32 = 0xAC00;
+const HANGUL_L_BASE: u32 = 0x1100;
+const HANGUL_V_BASE: u32 = 0x1161;
+const HANGUL_T_BASE: u32 = 0x11A7;
+const HANGUL_L_COUNT: u32 = 19;
+const HANGUL_V_COUNT: u32 = 21;
+const HANGUL_T_COUNT: u32 = 28;
+const HANGUL_N_COUNT: u32 = HANGUL_V_COUNT * HANGUL_T_COUNT;
+const HANGUL_S_COUNT: u32 = HANGUL_L_COUNT * HANGUL_N_COUNT;
+
+/// Normalize the text to Unicode Normalization Form KC.  Compatibility characters are replaced
+/// with their plain forms, so `ﬁ` becomes `fi`, `²` becomes `2` and `ｶ` becomes `カ`, and
+/// combining marks are composed with their base, so `e` followed by U+0301 becomes `é`.
+pub fn nfkc(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    let mut chars = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        decompose(c, &mut chars);
+    }
+    reorder_marks(&mut chars);
+    compose(&chars)
+}
+
+fn combining_class(c: char) -> u8 {
+    COMBINING_CLASSES
+        .binary_search_by(|&(start, end, _)| {
+            if end < c {
+                std::cmp::Ordering::Less
+            } else if start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .map_or(0, |i| COMBINING_CLASSES[i].2)
+}
+
+/// Push the full compatibility decomposition of the character
+fn decompose(c: char, out: &mut Vec<char>) {
+    let s_index = (c as u32).wrapping_sub(HANGUL_S_BASE);
+    if s_index < HANGUL_S_COUNT {
+        let jamo = [
+            HANGUL_L_BASE + s_index / HANGUL_N_COUNT,
+            HANGUL_V_BASE + (s_index % HANGUL_N_COUNT) / HANGUL_T_COUNT,
+            HANGUL_T_BASE + s_index % HANGUL_T_COUNT,
+        ];
+        out.extend(
+            jamo.into_iter()
+                .filter(|&j| j != HANGUL_T_BASE)
+                .filter_map(char::from_u32),
+        );
+        return;
+    }
+    match DECOMPOSITIONS.binary_search_by_key(&c, |&(from, _)| from) {
+        Ok(i) => out.extend(DECOMPOSITIONS[i].1.chars()),
+        Err(_) => out.push(c),
+    }
+}
+
+/// Put each run of combining marks in the canonical order, by their combining class
+fn reorder_marks(chars: &mut [char]) {
+    let mut i = 0;
+    while i < chars.len() {
+        if combining_class(chars[i]) == 0 {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && combining_class(chars[i]) != 0 {
+            i += 1;
+        }
+        // The sort is stable, so marks of the same class keep their order
+        chars[start..i].sort_by_key(|&c| combining_class(c));
+    }
+}
+
+/// The character the pair canonically composes into, if any
+fn compose_pair(first: char, second: char) -> Option<char> {
+    let (first_u32, second_u32) = (first as u32, second as u32);
+    let l_index = first_u32.wrapping_sub(HANGUL_L_BASE);
+    let v_index = second_u32.wrapping_sub(HANGUL_V_BASE);
+    if l_index < HANGUL_L_COUNT && v_index < HANGUL_V_COUNT {
+        return char::from_u32(
+            HANGUL_S_BASE + (l_index * HANGUL_V_COUNT + v_index) * HANGUL_T_COUNT,
+        );
+    }
+    let s_index = first_u32.wrapping_sub(HANGUL_S_BASE);
+    let t_index = second_u32.wrapping_sub(HANGUL_T_BASE);
+    if s_index < HANGUL_S_COUNT
+        && s_index % HANGUL_T_COUNT == 0
+        && t_index > 0
+        && t_index < HANGUL_T_COUNT
+    {
+        return char::from_u32(first_u32 + t_index);
+    }
+    COMPOSITIONS
+        .binary_search_by_key(&(first, second), |&(a, b, _)| (a, b))
+        .ok()
+        .map(|i| COMPOSITIONS[i].2)
+}
+
+/// Compose the decomposed characters.  A combining mark joins the last starter unless a mark of
+/// the same or a lower class, or another starter, comes between them.
+fn compose(chars: &[char]) -> String {
+    let mut composed = String::with_capacity(chars.len());
+    let mut starter: Option<char> = None;
+    // Marks after the starter that didn't combine with it
+    let mut uncombined = Vec::new();
+    for &c in chars {
+        let class = combining_class(c);
+        let Some(current) = starter else {
+            if class == 0 {
+                starter = Some(c);
+            } else {
+                composed.push(c);
+            }
+            continue;
+        };
+        let blocked = uncombined
+            .last()
+            .is_some_and(|&last| combining_class(last) >= class || class == 0);
+        if !blocked {
+            if let Some(combined) = compose_pair(current, c) {
+                starter = Some(combined);
+                continue;
+            }
+        }
+        if class == 0 {
+            composed.push(current);
+            composed.extend(uncombined.drain(..));
+            starter = Some(c);
+        } else {
+            uncombined.push(c);
+        }
+    }
+    composed.extend(starter);
+    composed.extend(uncombined);
+    composed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::nfkc;
+
+    #[test]
+    fn test_nfkc_composes_marks() {
+        assert_eq!(nfkc("cafe\u{301}"), "café");
+        assert_eq!(nfkc("café"), "café");
+        // The marks are put in canonical order before composing
+        assert_eq!(nfkc("a\u{323}\u{302}"), nfkc("a\u{302}\u{323}"));
+        assert_eq!(nfkc("\u{1100}\u{1161}\u{11A8}"), "각");
+    }
+
+    #[test]
+    fn test_nfkc_folds_compatibility_characters() {
+        assert_eq!(nfkc("ＴＬＳ ﬁle"), "TLS file");
+        assert_eq!(nfkc("x² Ⅳ ｶ"), "x2 IV カ");
+        assert_eq!(nfkc("plain ascii"), "plain ascii");
+    }
+}
@@ -1,23 +1,167 @@
-use std::num::ParseIntError;
+use std::{num::ParseIntError, sync::OnceLock};
 
-use crate::error::{RFSeeError, RFSeeResult};
+use regex::Regex;
 
-const RFC_DELIMITER: &str = "\n\n";
+use crate::{
+    error::{RFSeeError, RFSeeResult},
+    RfcDetails, RfcNumber,
+};
 
-/// Parse raw `String` contents of RFC index and return `Vec` of `&str` for each item after
-/// splitting on `RFC_DELIMITER`
+/// Citation of an RFC number that was never issued
+const NOT_ISSUED: &str = "Not Issued.";
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+/// Publication date at the end of a citation, with a day for some April 1st RFCs
+const DATE_REGEX: &str = r"(?:\d{1,2} )?([A-Z][a-z]+) (\d{4})\.?$";
+/// An author such as `S. Crocker`, `D.L. Mills` or `Ph. Hallam-Baker`, starting with initials
+const AUTHOR_REGEX: &str = r"^(?:[A-Z][a-z]?\.[ -]?)+[^ .,].*$";
+/// Parenthesised fields that follow the date of a citation
+const CITATION_FIELDS: [&str; 9] = [
+    "Format:",
+    "Obsoletes",
+    "Obsoleted by",
+    "Updates",
+    "Updated by",
+    "Also",
+    "Status:",
+    "Stream:",
+    "DOI:",
+];
+
+/// Running header at the top of each page after the first, such as
+/// `RFC 8124                 WebSocket SDP Attribute              March 2017`
+const PAGE_HEADER_REGEX: &str = r"^RFC \d+ {2,}(?:\S.* {2,})?(?:\d{1,2} )?[A-Z][a-z]+ \d{4}$";
+/// Running footer at the bottom of each page, such as
+/// `Ravindranath & Salgueiro     Standards Track                    [Page 1]`
+const PAGE_FOOTER_REGEX: &str = r"\[Page \d+\]$";
+const FORM_FEED: char = '\x0c';
+/// Headings of the standard sections about the status, copyright and IPR of an RFC
+const BOILERPLATE_SECTIONS: [&str; 6] = [
+    "status of this memo",
+    "copyright notice",
+    "full copyright statement",
+    "intellectual property",
+    "intellectual property statement",
+    "ipr notices",
+];
+/// Heading of the RFC Editor funding note, which is only boilerplate when it follows the
+/// copyright or IPR sections
+const FUNDING_ACKNOWLEDGEMENT: &str = "acknowledgement";
+/// Fields of the first page header, such as `Request for Comments: 8124`
+const FRONT_MATTER_FIELDS: [&str; 9] = [
+    "Request for Comments:",
+    "RFC:",
+    "Category:",
+    "ISSN:",
+    "Obsoletes:",
+    "Updates:",
+    "Obsoleted by:",
+    "Updated by:",
+    "NIC:",
+];
+
+static DATE_RE: OnceLock<Regex> = OnceLock::new();
+static AUTHOR_RE: OnceLock<Regex> = OnceLock::new();
+static PAGE_HEADER_RE: OnceLock<Regex> = OnceLock::new();
+static PAGE_FOOTER_RE: OnceLock<Regex> = OnceLock::new();
+
+/// Number of lines taken by the header of the first page, such as `Request for Comments: 8124`
+/// and `Category: Standards Track`.  It starts at the first column and runs up to the indented
+/// title.  Returns 0 when the text doesn't start with such a header.
+fn front_matter_len(content: &str) -> usize {
+    let mut len = 0;
+    let mut has_field = false;
+    for line in content.lines() {
+        let line = line.trim_start_matches(FORM_FEED);
+        if line.starts_with(char::is_whitespace) && !line.trim().is_empty() {
+            break;
+        }
+        has_field |= FRONT_MATTER_FIELDS.iter().any(|f| line.starts_with(f));
+        len += 1;
+    }
+    if has_field {
+        len
+    } else {
+        0
+    }
+}
+
+/// Strip the text of an RFC down to its content by removing form feeds, the header of the first
+/// page, the running header and footer of each page, and the status, copyright and IPR
+/// boilerplate sections along with the RFC Editor funding note.  Section headings start at the
+/// first column and their bodies are indented, so a boilerplate section runs until the next line
+/// starting at the first column.
+pub fn clean_rfc_text(content: &str) -> String {
+    let header_re = PAGE_HEADER_RE.get_or_init(|| Regex::new(PAGE_HEADER_REGEX).unwrap());
+    let footer_re = PAGE_FOOTER_RE.get_or_init(|| Regex::new(PAGE_FOOTER_REGEX).unwrap());
+    let mut cleaned = String::with_capacity(content.len());
+    let mut in_boilerplate = false;
+    for line in content.lines().skip(front_matter_len(content)) {
+        let line = line.trim_end().trim_start_matches(FORM_FEED);
+        if header_re.is_match(line) || footer_re.is_match(line) {
+            continue;
+        }
+        if !line.is_empty() && !line.starts_with(char::is_whitespace) {
+            let heading = line.to_lowercase();
+            in_boilerplate = BOILERPLATE_SECTIONS.contains(&heading.as_str())
+                || (in_boilerplate && heading == FUNDING_ACKNOWLEDGEMENT);
+        }
+        if !in_boilerplate {
+            cleaned.push_str(line);
+            cleaned.push('\n');
+        }
+    }
+    cleaned
+}
+
+/// Parse raw `String` contents of RFC index and return `Vec` of `&str` for each citation.  A
+/// citation starts with the RFC number at the start of a line and continues over the following
+/// indented lines, so the header and its indented examples are skipped.
 pub fn parse_rfc_index(content: &str) -> RFSeeResult<Vec<&str>> {
-    let found = content.find("0001");
-    match found {
-        Some(idx) => {
-            let raw_rfcs = &content[idx..];
-            let splitted = raw_rfcs.split(RFC_DELIMITER).collect();
-            Ok(splitted)
+    let mut citations = Vec::new();
+    // Byte range of the citation being read
+    let mut current: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let text = line.trim_end();
+        if text.starts_with(|c: char| c.is_ascii_digit()) {
+            if let Some((from, to)) = current.take() {
+                citations.push(&content[from..to]);
+            }
+            current = Some((start, start + text.len()));
+        } else if text.starts_with(char::is_whitespace) && !text.trim().is_empty() {
+            if let Some((_, to)) = current.as_mut() {
+                *to = start + text.len();
+            }
+        } else if let Some((from, to)) = current.take() {
+            // A blank line or unindented text ends the citation
+            citations.push(&content[from..to]);
         }
-        None => Err(RFSeeError::ParseError(
-            "Unable to parse RFC index".to_string(),
-        )),
     }
+    if let Some((from, to)) = current {
+        citations.push(&content[from..to]);
+    }
+
+    if citations.is_empty() {
+        return Err(RFSeeError::ParseError(
+            "Unable to parse RFC index, no citations found".to_string(),
+        ));
+    }
+    Ok(citations)
 }
 
 /// Parse raw RFC `String` contents into the RFC number and its title
@@ -34,16 +178,169 @@ pub fn parse_rfc_details(rfc_content: &str) -> RFSeeResult<(i32, &str)> {
     }
 }
 
+/// Parse a raw RFC index citation, such as
+///
+/// ```text
+/// 1129 Internet Time Synchronization: The Network Time Protocol. D.L.
+///      Mills. October 1989. (Format: TXT, PS, PDF, HTML) (Also RFC1119)
+///      (Status: INFORMATIONAL) (DOI: 10.17487/RFC1129)
+/// ```
+///
+/// into the RFC number and its details
+pub fn parse_rfc_citation(raw_rfc: &str) -> RFSeeResult<(RfcNumber, RfcDetails)> {
+    let (rfc_num, citation) = parse_rfc_details(raw_rfc)?;
+    // Citations are wrapped over several lines
+    let citation = citation.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if citation == NOT_ISSUED {
+        let details = RfcDetails {
+            title: NOT_ISSUED.trim_end_matches('.').to_string(),
+            not_issued: true,
+            ..Default::default()
+        };
+        return Ok((rfc_num, details));
+    }
+    let (rest, fields) = split_citation_fields(&citation);
+
+    let mut details = RfcDetails::default();
+    for field in fields {
+        if let Some(formats) = field.strip_prefix("Format:") {
+            details.formats = split_list(formats);
+        } else if let Some(rfcs) = field.strip_prefix("Obsoleted by") {
+            details.obsoleted_by = parse_rfc_list(rfcs);
+        } else if let Some(rfcs) = field.strip_prefix("Obsoletes") {
+            details.obsoletes = parse_rfc_list(rfcs);
+        } else if let Some(rfcs) = field.strip_prefix("Updated by") {
+            details.updated_by = parse_rfc_list(rfcs);
+        } else if let Some(rfcs) = field.strip_prefix("Updates") {
+            details.updates = parse_rfc_list(rfcs);
+        } else if let Some(docs) = field.strip_prefix("Also") {
+            details.also = split_list(docs);
+        } else if let Some(status) = field.strip_prefix("Status:") {
+            details.status = Some(status.trim().to_string());
+        } else if let Some(stream) = field.strip_prefix("Stream:") {
+            details.stream = Some(stream.trim().to_string());
+        } else if let Some(doi) = field.strip_prefix("DOI:") {
+            details.doi = Some(doi.trim().to_string());
+        }
+    }
+
+    let date_re = DATE_RE.get_or_init(|| Regex::new(DATE_REGEX).unwrap());
+    let mut head = rest;
+    if let Some(captures) = date_re.captures(rest) {
+        if let Some(month) = MONTHS.iter().position(|m| *m == &captures[1]) {
+            details.month = Some(month as u8 + 1);
+            details.year = captures[2].parse().ok();
+            head = &rest[..captures.get(0).map_or(0, |m| m.start())];
+        }
+    }
+
+    let (title, authors) = split_title_and_authors(head.trim_end());
+    details.title = title.to_string();
+    details.authors = authors;
+    Ok((rfc_num, details))
+}
+
+/// Split the parenthesised fields off the end of a citation, returning the rest of the citation
+/// and the fields in order.  Titles may contain parentheses too, so only known fields are split off.
+fn split_citation_fields(citation: &str) -> (&str, Vec<&str>) {
+    let mut rest = citation.trim_end();
+    let mut fields = Vec::new();
+    while rest.ends_with(')') {
+        let mut depth = 0;
+        let mut open = None;
+        for (idx, c) in rest.char_indices().rev() {
+            match c {
+                ')' => depth += 1,
+                '(' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        open = Some(idx);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let Some(open) = open else { break };
+        let field = &rest[open + 1..rest.len() - 1];
+        if !CITATION_FIELDS.iter().any(|name| field.starts_with(name)) {
+            break;
+        }
+        fields.push(field);
+        rest = rest[..open].trim_end();
+    }
+    fields.reverse();
+    (rest, fields)
+}
+
+/// Split the title from the authors that follow it, which are a comma separated list of names
+/// starting with initials.  When the authors don't look like that, for example an organisation,
+/// they are taken to be everything after the last period.
+fn split_title_and_authors(head: &str) -> (&str, Vec<String>) {
+    let ends: Vec<usize> = head.match_indices(". ").map(|(idx, _)| idx).collect();
+    for &end in &ends {
+        let authors = parse_authors(&head[end + 2..]);
+        if let Some(authors) = authors {
+            return (&head[..end], authors);
+        }
+    }
+    match ends.last() {
+        Some(&end) => {
+            let author = head[end + 2..].trim_end_matches('.');
+            (&head[..end], vec![author.to_string()])
+        }
+        None => (head.trim_end_matches('.'), Vec::new()),
+    }
+}
+
+/// Parse a comma separated list of authors, returning `None` if any of them doesn't look like a
+/// name.  An `Ed.` following an author marks them as an editor and is kept with them.
+fn parse_authors(list: &str) -> Option<Vec<String>> {
+    let author_re = AUTHOR_RE.get_or_init(|| Regex::new(AUTHOR_REGEX).unwrap());
+    // The period ending the list is part of the abbreviation when the last author is an editor
+    let list = if list.ends_with(" Ed.") || list.ends_with(" Eds.") {
+        list
+    } else {
+        list.strip_suffix('.').unwrap_or(list)
+    };
+    let mut authors: Vec<String> = Vec::new();
+    for author in list.split(", ") {
+        match authors.last_mut() {
+            Some(last) if author == "Ed." || author == "Eds." => {
+                last.push_str(", ");
+                last.push_str(author);
+            }
+            _ if author_re.is_match(author) => authors.push(author.to_string()),
+            _ => return None,
+        }
+    }
+    Some(authors)
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Parse a list such as `RFC5077, RFC5246` into RFC numbers, ignoring other documents
+fn parse_rfc_list(list: &str) -> Vec<RfcNumber> {
+    list.split(',')
+        .filter_map(|item| item.trim().strip_prefix("RFC")?.parse().ok())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{parse_rfc_details, parse_rfc_index};
+    use super::{clean_rfc_text, parse_rfc_citation, parse_rfc_details, parse_rfc_index};
 
     #[test]
     fn test_parse_index() {
         let index_contents = std::fs::read_to_string("../../data/rfc_index.txt").unwrap();
         let parsed = parse_rfc_index(&index_contents).unwrap();
 
-        assert_eq!(parsed, vec!["0001 Host Software. S. Crocker. April 1969. (Format: TXT, HTML) (Status:\n     UNKNOWN) (DOI: 10.17487/RFC0001) ", "0002 Host software. B. Duvall. April 1969. (Format: TXT, PDF, HTML)\n     (Status: UNKNOWN) (DOI: 10.17487/RFC0002) ", ""]);
+        assert_eq!(parsed, vec!["0001 Host Software. S. Crocker. April 1969. (Format: TXT, HTML) (Status:\n     UNKNOWN) (DOI: 10.17487/RFC0001)", "0002 Host software. B. Duvall. April 1969. (Format: TXT, PDF, HTML)\n     (Status: UNKNOWN) (DOI: 10.17487/RFC0002)"]);
     }
 
     #[test]
@@ -53,6 +350,125 @@ mod tests {
         let first = parsed.first().unwrap();
         let (num, title) = parse_rfc_details(first).unwrap();
         assert_eq!(num, 1);
-        assert_eq!(title, "Host Software. S. Crocker. April 1969. (Format: TXT, HTML) (Status:\n     UNKNOWN) (DOI: 10.17487/RFC0001) ");
+        assert_eq!(title, "Host Software. S. Crocker. April 1969. (Format: TXT, HTML) (Status:\n     UNKNOWN) (DOI: 10.17487/RFC0001)");
+    }
+
+    #[test]
+    fn test_parse_rfc_citation() {
+        let raw = "8446 The Transport Layer Security (TLS) Protocol Version 1.3. E.
+     Rescorla. August 2018. (Format: HTML, TXT, PDF, XML) (Obsoletes
+     RFC5077, RFC5246, RFC6961) (Updates RFC5705, RFC6066) (Updated by
+     RFC9846) (Status: PROPOSED STANDARD) (Stream: IETF) (DOI:
+     10.17487/RFC8446) ";
+        let (num, details) = parse_rfc_citation(raw).unwrap();
+        assert_eq!(num, 8446);
+        assert_eq!(
+            details.title,
+            "The Transport Layer Security (TLS) Protocol Version 1.3"
+        );
+        assert_eq!(details.authors, vec!["E. Rescorla"]);
+        assert_eq!((details.month, details.year), (Some(8), Some(2018)));
+        assert_eq!(details.formats, vec!["HTML", "TXT", "PDF", "XML"]);
+        assert_eq!(details.obsoletes, vec![5077, 5246, 6961]);
+        assert_eq!(details.updates, vec![5705, 6066]);
+        assert_eq!(details.updated_by, vec![9846]);
+        assert!(details.obsoleted_by.is_empty());
+        assert_eq!(details.status.as_deref(), Some("PROPOSED STANDARD"));
+        assert_eq!(details.stream.as_deref(), Some("IETF"));
+        assert_eq!(details.doi.as_deref(), Some("10.17487/RFC8446"));
+    }
+
+    #[test]
+    fn test_clean_rfc_text() {
+        let content = std::fs::read_to_string("../../data/rfc_8124.txt").unwrap();
+        let cleaned = clean_rfc_text(&content);
+        assert!(!cleaned.contains('\x0c'));
+        assert!(!cleaned.contains("[Page"));
+        assert!(!cleaned.contains("WebSocket SDP Attribute"));
+        assert!(!cleaned.contains("Status of This Memo"));
+        assert!(!cleaned.contains("Copyright (c) 2017 IETF Trust"));
+        assert!(!cleaned.contains("Request for Comments: 8124"));
+        assert!(!cleaned.contains("ISSN"));
+        assert!(cleaned.starts_with("                 The Session Description Protocol (SDP)\n"));
+        assert!(cleaned.contains("The WebSocket protocol enables bidirectional real-time"));
+        assert!(cleaned.contains("Table of Contents"));
+        assert!(cleaned.contains("Authors' Addresses"));
+    }
+
+    #[test]
+    fn test_clean_rfc_text_keeps_content() {
+        let content = "1.  Introduction\n\n   RFC 8124 defines   an attribute from March 2017\n\nAcknowledgement\n\n   Thanks to the reviewers.\n\nFull Copyright Statement\n\n   Copyright (C) The Internet Society (2004).\n\nAcknowledgement\n\n   Funding for the RFC Editor function is currently provided by the\n   Internet Society.\n";
+        assert_eq!(
+            clean_rfc_text(content),
+            "1.  Introduction\n\n   RFC 8124 defines   an attribute from March 2017\n\nAcknowledgement\n\n   Thanks to the reviewers.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_clean_rfc_text_front_matter() {
+        let content = "Network Working Group                                          J. Smith\nRequest for Comments: 3999                                    Example Inc\nObsoletes: 2999                                                June 2004\nCategory: Informational\n\n\n                          An Example Protocol\n\nAbstract\n";
+        assert_eq!(
+            clean_rfc_text(content),
+            "                          An Example Protocol\n\nAbstract\n"
+        );
+        // Without any header fields the first lines are content
+        let content = "Introduction\n\n   Some text.\n";
+        assert_eq!(clean_rfc_text(content), content);
+    }
+
+    #[test]
+    fn test_parse_index_lines() {
+        let index_contents = "RFC INDEX\n\n  1129 Example in the header.\n       October 1989.\n\n0001 Host Software.\n     S. Crocker.\r\n0002 Not Issued.\n\n\n0x03 Malformed.\n     April 1969.\n\n";
+        let parsed = parse_rfc_index(index_contents).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                "0001 Host Software.\n     S. Crocker.",
+                "0002 Not Issued.",
+                "0x03 Malformed.\n     April 1969."
+            ]
+        );
+        assert!(parse_rfc_citation(parsed[2]).is_err());
+        assert!(parse_rfc_index("RFC INDEX\n\n  #### Not Issued.\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_not_issued_citation() {
+        let (num, details) = parse_rfc_citation("0026 Not Issued.\n").unwrap();
+        assert_eq!(num, 26);
+        assert!(details.not_issued);
+        assert_eq!(details.title, "Not Issued");
+
+        let (_, details) =
+            parse_rfc_citation("0001 Host Software. S. Crocker. April 1969.").unwrap();
+        assert!(!details.not_issued);
+    }
+
+    #[test]
+    fn test_parse_rfc_citation_authors() {
+        let raw = "1129 Internet Time Synchronization: The Network Time Protocol. D.L.
+       Mills. October 1989. (Format: TXT, PS, PDF, HTML) (Also RFC1119) 
+       (Status: INFORMATIONAL) (DOI: 10.17487/RFC1129) ";
+        let (_, details) = parse_rfc_citation(raw).unwrap();
+        assert_eq!(
+            details.title,
+            "Internet Time Synchronization: The Network Time Protocol"
+        );
+        assert_eq!(details.authors, vec!["D.L. Mills"]);
+        assert_eq!(details.also, vec!["RFC1119"]);
+
+        let raw = "3514 The Security Flag in the IPv4 Header. S. Bellovin, J. Postel, Ed.
+     1 April 2003. (Format: TXT) (Status: INFORMATIONAL)";
+        let (_, details) = parse_rfc_citation(raw).unwrap();
+        assert_eq!(details.title, "The Security Flag in the IPv4 Header");
+        assert_eq!(details.authors, vec!["S. Bellovin", "J. Postel, Ed."]);
+        assert_eq!((details.month, details.year), (Some(4), Some(2003)));
+
+        let raw = "2146 U.S. Government Internet Domain Names. Federal Networking
+     Council. May 1997. (Format: TXT) (Obsoleted by RFC9999) (Status: INFORMATIONAL)";
+        let (_, details) = parse_rfc_citation(raw).unwrap();
+        assert_eq!(details.title, "U.S. Government Internet Domain Names");
+        assert_eq!(details.authors, vec!["Federal Networking Council"]);
+        assert_eq!(details.obsoleted_by, vec![9999]);
     }
 }
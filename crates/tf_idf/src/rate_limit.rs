@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    sync::{Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The smallest interval between requests to a host after it has responded with a 429
+const MIN_THROTTLED_INTERVAL: Duration = Duration::from_millis(250);
+/// The largest interval between requests to a host, however often it responds with a 429
+const MAX_THROTTLED_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Limits on the requests made to each host
+#[derive(Clone, Debug, Default)]
+pub struct RateLimit {
+    /// Maximum number of requests started per second, `None` for no limit
+    pub requests_per_second: Option<f64>,
+    /// Maximum number of requests in flight at the same time, `None` for no limit
+    pub max_in_flight: Option<usize>,
+}
+
+/// Request bookkeeping for a single host
+#[derive(Debug)]
+struct HostState {
+    /// Time between the start of consecutive requests
+    interval: Duration,
+    /// Earliest time the next request may start
+    next_start: Instant,
+    in_flight: usize,
+}
+
+/// Rate limiter shared by all the threads making requests, which keeps each host within its
+/// `RateLimit`
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    hosts: Mutex<HashMap<String, HostState>>,
+    /// Notified whenever a request finishes and frees an in flight slot
+    finished: Condvar,
+}
+
+/// Permission to make a request to a host, the in flight slot is released when dropped
+pub struct Permit<'a> {
+    limiter: &'a RateLimiter,
+    host: String,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut hosts = self.limiter.hosts.lock().unwrap();
+        if let Some(state) = hosts.get_mut(&self.host) {
+            state.in_flight -= 1;
+        }
+        self.limiter.finished.notify_all();
+    }
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            ..Default::default()
+        }
+    }
+
+    /// Block until a request to the host is allowed to start
+    pub fn acquire(&self, host: &str) -> Permit<'_> {
+        let interval = match self.limit.requests_per_second {
+            Some(rps) if rps > 0.0 => Duration::from_secs_f64(1.0 / rps),
+            _ => Duration::ZERO,
+        };
+        let max_in_flight = self.limit.max_in_flight.unwrap_or(usize::MAX).max(1);
+
+        let mut hosts = self.hosts.lock().unwrap();
+        loop {
+            let state = hosts.entry(host.to_string()).or_insert_with(|| HostState {
+                interval,
+                next_start: Instant::now(),
+                in_flight: 0,
+            });
+            if state.in_flight < max_in_flight {
+                // Reserve the next start time so that waiting requests start in order
+                let start = state.next_start.max(Instant::now());
+                state.next_start = start + state.interval;
+                state.in_flight += 1;
+                drop(hosts);
+                std::thread::sleep(start.saturating_duration_since(Instant::now()));
+                return Permit {
+                    limiter: self,
+                    host: host.to_string(),
+                };
+            }
+            hosts = self.finished.wait(hosts).unwrap();
+        }
+    }
+
+    /// Slow down requests to a host that responded with a 429.  The interval between requests is
+    /// doubled and no request starts before the `Retry-After` delay, when one was given.
+    pub fn throttle(&self, host: &str, retry_after: Option<Duration>) {
+        let mut hosts = self.hosts.lock().unwrap();
+        if let Some(state) = hosts.get_mut(host) {
+            state.interval =
+                (state.interval * 2).clamp(MIN_THROTTLED_INTERVAL, MAX_THROTTLED_INTERVAL);
+            let resume = Instant::now() + retry_after.unwrap_or(state.interval);
+            state.next_start = state.next_start.max(resume);
+        }
+    }
+}
+
+/// Parse the value of a `Retry-After` header given in seconds.  The HTTP date form isn't
+/// supported, in which case the limiter falls back to its own interval.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use super::{parse_retry_after, RateLimit, RateLimiter};
+
+    #[test]
+    fn test_requests_are_spaced() {
+        let limiter = RateLimiter::new(RateLimit {
+            requests_per_second: Some(20.0),
+            max_in_flight: None,
+        });
+        let start = Instant::now();
+        for _ in 0..3 {
+            drop(limiter.acquire("www.rfc-editor.org"));
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_in_flight_requests_are_limited() {
+        let limiter = Arc::new(RateLimiter::new(RateLimit {
+            requests_per_second: None,
+            max_in_flight: Some(1),
+        }));
+        let permit = limiter.acquire("www.rfc-editor.org");
+
+        let cloned = Arc::clone(&limiter);
+        let start = Instant::now();
+        let waiting = std::thread::spawn(move || {
+            drop(cloned.acquire("www.rfc-editor.org"));
+            start.elapsed()
+        });
+        std::thread::sleep(Duration::from_millis(100));
+        drop(permit);
+        assert!(waiting.join().unwrap() >= Duration::from_millis(100));
+
+        // Other hosts have their own limit
+        let _permit = limiter.acquire("www.rfc-editor.org");
+        drop(limiter.acquire("www.ietf.org"));
+    }
+
+    #[test]
+    fn test_throttle_waits_for_retry_after() {
+        let limiter = RateLimiter::new(RateLimit::default());
+        drop(limiter.acquire("www.rfc-editor.org"));
+        limiter.throttle("www.rfc-editor.org", parse_retry_after("1"));
+        let start = Instant::now();
+        drop(limiter.acquire("www.rfc-editor.org"));
+        assert!(start.elapsed() >= Duration::from_millis(900));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+}
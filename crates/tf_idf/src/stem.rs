@@ -0,0 +1,291 @@
+/// Suffixes replaced in step 2, grouped by their second to last letter as only one group can match
+const STEP2_SUFFIXES: [(&str, &str); 21] = [
+    ("ational", "ate"),
+    ("tional", "tion"),
+    ("enci", "ence"),
+    ("anci", "ance"),
+    ("izer", "ize"),
+    ("bli", "ble"),
+    ("alli", "al"),
+    ("entli", "ent"),
+    ("eli", "e"),
+    ("ousli", "ous"),
+    ("ization", "ize"),
+    ("ation", "ate"),
+    ("ator", "ate"),
+    ("alism", "al"),
+    ("iveness", "ive"),
+    ("fulness", "ful"),
+    ("ousness", "ous"),
+    ("aliti", "al"),
+    ("iviti", "ive"),
+    ("biliti", "ble"),
+    ("logi", "log"),
+];
+const STEP3_SUFFIXES: [(&str, &str); 7] = [
+    ("icate", "ic"),
+    ("ative", ""),
+    ("alize", "al"),
+    ("iciti", "ic"),
+    ("ical", "ic"),
+    ("ful", ""),
+    ("ness", ""),
+];
+/// Suffixes removed in step 4, `ion` is handled separately as it must follow an `s` or `t`
+const STEP4_SUFFIXES: [&str; 18] = [
+    "al", "ance", "ence", "er", "ic", "able", "ible", "ant", "ement", "ment", "ent", "ou", "ism",
+    "ate", "iti", "ous", "ive", "ize",
+];
+
+/// Reduce an English word to its stem with the Porter stemming algorithm, so `routing`, `routes`
+/// and `routed` all become `rout`.  Agent nouns with a short stem, such as `router`, also lose
+/// their `-er`, which Porter keeps.  Only lowercase ASCII words of more than two letters are
+/// stemmed, anything else, such as numbers and identifiers, is returned as it is.
+pub fn stem(word: &str) -> String {
+    if word.len() <= 2 || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return word.to_string();
+    }
+    let mut stemmer = Stemmer {
+        b: word.as_bytes().to_vec(),
+        j: 0,
+    };
+    stemmer.step1ab();
+    if stemmer.b.len() > 1 {
+        stemmer.step1c();
+        stemmer.step2();
+        stemmer.step3();
+        stemmer.step4();
+        stemmer.step4_agent_noun();
+        stemmer.step5();
+    }
+    // The word was ASCII and only ASCII is written
+    String::from_utf8(stemmer.b).unwrap()
+}
+
+/// The word being stemmed.  `j` is the length of the stem once the suffix found by `ends` is
+/// removed.
+struct Stemmer {
+    b: Vec<u8>,
+    j: usize,
+}
+
+impl Stemmer {
+    fn is_consonant(&self, i: usize) -> bool {
+        match self.b[i] {
+            b'a' | b'e' | b'i' | b'o' | b'u' => false,
+            b'y' => i == 0 || !self.is_consonant(i - 1),
+            _ => true,
+        }
+    }
+
+    /// The number of vowel consonant sequences in the stem, `m` in the algorithm
+    fn measure(&self) -> usize {
+        let mut n = 0;
+        let mut i = 0;
+        // Skip the leading consonants
+        while i < self.j && self.is_consonant(i) {
+            i += 1;
+        }
+        loop {
+            while i < self.j && !self.is_consonant(i) {
+                i += 1;
+            }
+            if i >= self.j {
+                return n;
+            }
+            while i < self.j && self.is_consonant(i) {
+                i += 1;
+            }
+            n += 1;
+        }
+    }
+
+    fn vowel_in_stem(&self) -> bool {
+        (0..self.j).any(|i| !self.is_consonant(i))
+    }
+
+    /// Whether the word ends with a double consonant up to `len`
+    fn double_consonant(&self, len: usize) -> bool {
+        len >= 2 && self.b[len - 1] == self.b[len - 2] && self.is_consonant(len - 1)
+    }
+
+    /// Whether the word up to `len` ends consonant vowel consonant, where the last consonant isn't
+    /// `w`, `x` or `y`, such as `hop`
+    fn cvc(&self, len: usize) -> bool {
+        len >= 3
+            && self.is_consonant(len - 3)
+            && !self.is_consonant(len - 2)
+            && self.is_consonant(len - 1)
+            && !matches!(self.b[len - 1], b'w' | b'x' | b'y')
+    }
+
+    /// Whether the word ends with the suffix, setting the stem length if it does
+    fn ends(&mut self, suffix: &str) -> bool {
+        if self.b.ends_with(suffix.as_bytes()) {
+            self.j = self.b.len() - suffix.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Replace the suffix found by `ends` with the replacement
+    fn set_to(&mut self, replacement: &str) {
+        self.b.truncate(self.j);
+        self.b.extend_from_slice(replacement.as_bytes());
+    }
+
+    /// Replace the suffix found by `ends` if the stem has at least one vowel consonant sequence
+    fn replace(&mut self, replacement: &str) {
+        if self.measure() > 0 {
+            self.set_to(replacement);
+        }
+    }
+
+    /// Remove plurals and `-ed` or `-ing`
+    fn step1ab(&mut self) {
+        if self.b.ends_with(b"s") {
+            if self.ends("sses") || self.ends("ies") {
+                self.b.truncate(self.b.len() - 2);
+            } else if !self.b.ends_with(b"ss") {
+                self.b.pop();
+            }
+        }
+        if self.ends("eed") {
+            if self.measure() > 0 {
+                self.b.pop();
+            }
+        } else if (self.ends("ed") || self.ends("ing")) && self.vowel_in_stem() {
+            self.b.truncate(self.j);
+            if self.ends("at") || self.ends("bl") || self.ends("iz") {
+                self.b.push(b'e');
+            } else if self.double_consonant(self.b.len()) {
+                if !matches!(self.b[self.b.len() - 1], b'l' | b's' | b'z') {
+                    self.b.pop();
+                }
+            } else {
+                self.j = self.b.len();
+                if self.measure() == 1 && self.cvc(self.b.len()) {
+                    self.b.push(b'e');
+                }
+            }
+        }
+    }
+
+    /// Turn a final `y` into `i` when there is another vowel in the stem
+    fn step1c(&mut self) {
+        if self.ends("y") && self.vowel_in_stem() {
+            let last = self.b.len() - 1;
+            self.b[last] = b'i';
+        }
+    }
+
+    /// Map double suffixes to single ones, such as `-ization` to `-ize`
+    fn step2(&mut self) {
+        if let Some((_, replacement)) = STEP2_SUFFIXES.iter().find(|(s, _)| self.ends(s)) {
+            self.replace(replacement);
+        }
+    }
+
+    /// Handle `-ic-`, `-full` and `-ness`
+    fn step3(&mut self) {
+        if let Some((_, replacement)) = STEP3_SUFFIXES.iter().find(|(s, _)| self.ends(s)) {
+            self.replace(replacement);
+        }
+    }
+
+    /// Remove `-ant`, `-ence` and the like when the stem is long enough
+    fn step4(&mut self) {
+        let found = (self.ends("ion") && self.j > 0 && matches!(self.b[self.j - 1], b's' | b't'))
+            || STEP4_SUFFIXES.iter().any(|s| self.ends(s));
+        if found && self.measure() > 1 {
+            self.b.truncate(self.j);
+        }
+    }
+
+    /// Remove the `-er` of agent nouns such as `router` and `reader`, whose stem is too short for
+    /// step 4.  Only stems ending in a vowel pair or a single vowel and one consonant lose it, so
+    /// `water`, `paper` and `plaster` keep their form.
+    fn step4_agent_noun(&mut self) {
+        if self.ends("er")
+            && self.j >= 2
+            && self.is_consonant(self.j - 1)
+            && !self.is_consonant(self.j - 2)
+            && !self.cvc(self.j)
+            && self.measure() == 1
+        {
+            self.b.truncate(self.j);
+        }
+    }
+
+    /// Remove a final `-e` and change `-ll` to `-l` when the stem is long enough
+    fn step5(&mut self) {
+        self.j = self.b.len();
+        if self.b.ends_with(b"e") {
+            self.j -= 1;
+            let measure = self.measure();
+            if measure > 1 || (measure == 1 && !self.cvc(self.j)) {
+                self.b.pop();
+            }
+        }
+        self.j = self.b.len();
+        if self.b.ends_with(b"l") && self.double_consonant(self.b.len()) && self.measure() > 1 {
+            self.b.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stem;
+
+    #[test]
+    fn test_stem() {
+        let cases = [
+            ("caresses", "caress"),
+            ("ponies", "poni"),
+            ("cats", "cat"),
+            ("agreed", "agre"),
+            ("plastered", "plaster"),
+            ("motoring", "motor"),
+            ("conflated", "conflat"),
+            ("hopping", "hop"),
+            ("falling", "fall"),
+            ("filing", "file"),
+            ("happy", "happi"),
+            ("relational", "relat"),
+            ("generalization", "gener"),
+            ("electrical", "electr"),
+            ("adjustment", "adjust"),
+            ("adoption", "adopt"),
+            ("controlling", "control"),
+            ("connections", "connect"),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(stem(word), expected, "stem of {word}");
+        }
+    }
+
+    #[test]
+    fn test_stem_matches_word_forms() {
+        assert_eq!(stem("routing"), "rout");
+        assert_eq!(stem("routes"), "rout");
+        assert_eq!(stem("routed"), "rout");
+        assert_eq!(stem("router"), "rout");
+        assert_eq!(stem("routers"), "rout");
+        assert_eq!(stem("reader"), "read");
+        assert_eq!(stem("reading"), "read");
+        // Short vowels and consonant clusters keep the `-er`
+        assert_eq!(stem("water"), "water");
+        assert_eq!(stem("paper"), "paper");
+        assert_eq!(stem("plaster"), "plaster");
+    }
+
+    #[test]
+    fn test_stem_skips_other_terms() {
+        assert_eq!(stem("is"), "is");
+        assert_eq!(stem("x509"), "x509");
+        assert_eq!(stem("max_frame_size"), "max_frame_size");
+        assert_eq!(stem("straße"), "straße");
+    }
+}
@@ -0,0 +1,260 @@
+use std::{collections::BTreeSet, path::Path, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{RFSeeError, RFSeeResult},
+    tokenize::split_terms,
+    Term,
+};
+
+/// Common English words
+const ENGLISH_STOPWORDS: &[&str] = &[
+    "a",
+    "about",
+    "above",
+    "after",
+    "again",
+    "against",
+    "all",
+    "am",
+    "an",
+    "and",
+    "any",
+    "are",
+    "as",
+    "at",
+    "be",
+    "because",
+    "been",
+    "before",
+    "being",
+    "below",
+    "between",
+    "both",
+    "but",
+    "by",
+    "can",
+    "could",
+    "did",
+    "do",
+    "does",
+    "doing",
+    "down",
+    "during",
+    "each",
+    "few",
+    "for",
+    "from",
+    "further",
+    "had",
+    "has",
+    "have",
+    "having",
+    "he",
+    "her",
+    "here",
+    "hers",
+    "herself",
+    "him",
+    "himself",
+    "his",
+    "how",
+    "i",
+    "if",
+    "in",
+    "into",
+    "is",
+    "it",
+    "its",
+    "itself",
+    "just",
+    "me",
+    "more",
+    "most",
+    "my",
+    "myself",
+    "no",
+    "nor",
+    "not",
+    "now",
+    "of",
+    "off",
+    "on",
+    "once",
+    "only",
+    "or",
+    "other",
+    "our",
+    "ours",
+    "ourselves",
+    "out",
+    "over",
+    "own",
+    "same",
+    "she",
+    "so",
+    "some",
+    "such",
+    "than",
+    "that",
+    "the",
+    "their",
+    "theirs",
+    "them",
+    "themselves",
+    "then",
+    "there",
+    "these",
+    "they",
+    "this",
+    "those",
+    "through",
+    "to",
+    "too",
+    "under",
+    "until",
+    "up",
+    "very",
+    "was",
+    "we",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "while",
+    "who",
+    "whom",
+    "why",
+    "will",
+    "with",
+    "would",
+    "you",
+    "your",
+    "yours",
+    "yourself",
+    "yourselves",
+];
+/// Words found in nearly every RFC, from the page headers and footers, the boilerplate and the
+/// requirement levels of BCP 14
+const RFC_STOPWORDS: &[&str] = &[
+    "rfc",
+    "rfcs",
+    "internet",
+    "draft",
+    "page",
+    "section",
+    "sections",
+    "memo",
+    "document",
+    "et",
+    "al",
+    "must",
+    "shall",
+    "should",
+    "may",
+    "required",
+    "recommended",
+    "optional",
+    "bcp",
+];
+/// Starts a comment in a stopwords file
+const COMMENT_PREFIX: char = '#';
+
+static ENGLISH: OnceLock<BTreeSet<&'static str>> = OnceLock::new();
+static RFC: OnceLock<BTreeSet<&'static str>> = OnceLock::new();
+
+/// Terms that are left out of the index and search queries because they appear in nearly every
+/// RFC.  The built-in English and RFC lists can be turned off and extended with a file.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Stopwords {
+    /// Drop common English words such as `the` and `of`
+    pub english: bool,
+    /// Drop words such as `section` and `must` that are in nearly every RFC
+    pub rfc: bool,
+    /// Additional stopwords, such as those read from a file
+    pub custom: BTreeSet<Term>,
+}
+
+impl Default for Stopwords {
+    fn default() -> Self {
+        Self {
+            english: true,
+            rfc: true,
+            custom: BTreeSet::new(),
+        }
+    }
+}
+
+impl Stopwords {
+    /// Whether the term, as produced by the tokenizer, is a stopword
+    pub fn contains(&self, term: &str) -> bool {
+        (self.english
+            && ENGLISH
+                .get_or_init(|| ENGLISH_STOPWORDS.iter().copied().collect())
+                .contains(term))
+            || (self.rfc
+                && RFC
+                    .get_or_init(|| RFC_STOPWORDS.iter().copied().collect())
+                    .contains(term))
+            || self.custom.contains(term)
+    }
+
+    /// Add the stopwords in the file, which are separated by whitespace.  Everything after a `#`
+    /// on a line is a comment.  Words are folded like the RFCs are, so `MUST` matches `must`.
+    pub fn add_file(&mut self, path: &Path) -> RFSeeResult<()> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            RFSeeError::IOError(format!("Unable to read stopwords {}: {e}", path.display()))
+        })?;
+        for line in contents.lines() {
+            let words = line.split(COMMENT_PREFIX).next().unwrap_or_default();
+            self.custom.extend(split_terms(words));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stopwords;
+
+    #[test]
+    fn test_default_stopwords() {
+        let stopwords = Stopwords::default();
+        assert!(stopwords.contains("the"));
+        assert!(stopwords.contains("section"));
+        assert!(!stopwords.contains("tls"));
+        // Only the `BCP` of `BCP 14` is dropped, numbers are kept
+        assert!(stopwords.contains("bcp"));
+        assert!(!stopwords.contains("14"));
+
+        let english_only = Stopwords {
+            rfc: false,
+            ..Default::default()
+        };
+        assert!(english_only.contains("the"));
+        assert!(!english_only.contains("section"));
+    }
+
+    #[test]
+    fn test_stopwords_file() {
+        let path = std::env::temp_dir().join("rfsee-test-stopwords.txt");
+        std::fs::write(
+            &path,
+            "# Words to skip\nIANA Considerations\nOctet # trailing\n",
+        )
+        .unwrap();
+        let mut stopwords = Stopwords::default();
+        let res = stopwords.add_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        res.unwrap();
+        assert_eq!(
+            stopwords.custom.iter().collect::<Vec<_>>(),
+            vec!["considerations", "iana", "octet"]
+        );
+        assert!(stopwords.contains("iana"));
+        assert!(!stopwords.contains("trailing"));
+    }
+}
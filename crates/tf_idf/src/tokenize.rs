@@ -0,0 +1,128 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{normalize::nfkc, stem::stem, stopwords::Stopwords, Term};
+
+const WORD_MATCH_REGEX: &str = r"(\w+)";
+/// Joins the parts of identifiers such as `max_frame_size`
+const IDENTIFIER_SEPARATOR: char = '_';
+
+static WORD_RE: OnceLock<Regex> = OnceLock::new();
+
+/// Split text into terms.  Text is NFKC normalized and lowercased, so `Hello`, `HELLO` and
+/// `ｈｅｌｌｏ` are the same term.  Identifiers joined with `_` produce the whole identifier followed
+/// by each part, so `max_frame_size` is found when searching for either it or `frame`.
+pub fn split_terms(text: &str) -> Vec<Term> {
+    let normalized = nfkc(text).to_lowercase();
+    let word_re = WORD_RE.get_or_init(|| Regex::new(WORD_MATCH_REGEX).unwrap());
+    let mut terms = Vec::new();
+    for found in word_re.find_iter(&normalized) {
+        let word = found.as_str().trim_matches(IDENTIFIER_SEPARATOR);
+        if word.is_empty() {
+            continue;
+        }
+        terms.push(word.to_string());
+        if word.contains(IDENTIFIER_SEPARATOR) {
+            terms.extend(
+                word.split(IDENTIFIER_SEPARATOR)
+                    .filter(|part| !part.is_empty())
+                    .map(|part| part.to_string()),
+            );
+        }
+    }
+    terms
+}
+
+/// Turns RFCs and search queries into the terms that are indexed and searched for.  It is saved
+/// with the index so that queries are tokenized the same way as the RFCs were.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Tokenizer {
+    pub stopwords: Stopwords,
+    /// Reduce terms to their stem, so `routing` and `routes` are the same term
+    pub stem: bool,
+}
+
+impl Tokenizer {
+    /// Split the text into terms, leaving out stopwords and stemming the rest if enabled
+    pub fn tokenize(&self, text: &str) -> Vec<Term> {
+        split_terms(text)
+            .into_iter()
+            .filter(|term| !self.stopwords.contains(term))
+            .map(|term| if self.stem { stem(&term) } else { term })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_terms, Tokenizer};
+    use crate::stopwords::Stopwords;
+
+    #[test]
+    fn test_split_terms_folds_case_and_unicode() {
+        assert_eq!(split_terms("Hello, HELLO hello!"), vec!["hello"; 3]);
+        // Fullwidth letters and ligatures are folded into their plain forms
+        assert_eq!(split_terms("ＴＬＳ ﬁle"), vec!["tls", "file"]);
+        assert_eq!(
+            split_terms("ｍａｘ＿ｆｒａｍｅ ﬀ ﬆ"),
+            vec!["max_frame", "max", "frame", "ff", "st"]
+        );
+        assert_eq!(split_terms("Straße"), vec!["straße"]);
+        // Decomposed and precomposed accents are the same term
+        assert_eq!(split_terms("café cafe\u{301}"), vec!["café"; 2]);
+        assert_eq!(split_terms("x² Ⅳ ｶ"), vec!["x2", "iv", "カ"]);
+    }
+
+    #[test]
+    fn test_split_terms_identifiers() {
+        assert_eq!(
+            split_terms("SETTINGS_MAX_FRAME_SIZE"),
+            vec![
+                "settings_max_frame_size",
+                "settings",
+                "max",
+                "frame",
+                "size"
+            ]
+        );
+        assert_eq!(split_terms("__init__ x__y"), vec!["init", "x__y", "x", "y"]);
+        assert!(split_terms("___ - ...").is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_drops_stopwords() {
+        let tokenizer = Tokenizer::default();
+        assert_eq!(
+            tokenizer.tokenize("The client MUST send the TLS handshake"),
+            vec!["client", "send", "tls", "handshake"]
+        );
+        let tokenizer = Tokenizer {
+            stopwords: Stopwords {
+                english: false,
+                rfc: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(tokenizer.tokenize("The TLS"), vec!["the", "tls"]);
+    }
+
+    #[test]
+    fn test_tokenize_stems() {
+        let tokenizer = Tokenizer {
+            stem: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            tokenizer.tokenize("Routing the routes of a router and routers"),
+            vec!["rout", "rout", "rout", "rout"]
+        );
+        assert_eq!(
+            Tokenizer::default().tokenize("Routing the routes"),
+            vec!["routing", "routes"]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt::Display;
+
+use crate::error::{RFSeeError, RFSeeResult};
+
+/// Schemes the HTTP client can fetch
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    Http,
+    Https,
+}
+
+impl Scheme {
+    /// Port used when the URL doesn't have one
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Scheme::Http => 80,
+            Scheme::Https => 443,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scheme::Http => "http",
+            Scheme::Https => "https",
+        }
+    }
+}
+
+/// An absolute `http` or `https` URL.  Any fragment is dropped as it is never sent to the server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Url {
+    pub scheme: Scheme,
+    pub host: String,
+    /// Port given explicitly in the URL
+    pub port: Option<u16>,
+    /// Path, always starting with `/`
+    pub path: String,
+    /// Query without the leading `?`
+    pub query: Option<String>,
+}
+
+impl Url {
+    /// Parse an absolute URL such as `http://localhost:8080/rfc/rfc1.txt?x=y`
+    pub fn parse(url: &str) -> RFSeeResult<Self> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| RFSeeError::ParseError(format!("Invalid URL, missing scheme: {url}")))?;
+        let scheme = match scheme.to_ascii_lowercase().as_str() {
+            "http" => Scheme::Http,
+            "https" => Scheme::Https,
+            _ => {
+                return Err(RFSeeError::ParseError(format!(
+                    "Unsupported URL scheme {scheme} in {url}, only http and https are supported"
+                )))
+            }
+        };
+
+        let rest = rest.split('#').next().unwrap_or_default();
+        let authority_end = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (authority, path_and_query) = rest.split_at(authority_end);
+        let (path, query) = match path_and_query.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_string())),
+            None => (path_and_query, None),
+        };
+        let path = if path.is_empty() {
+            "/".to_string()
+        } else {
+            path.to_string()
+        };
+
+        // Credentials aren't used for fetching, so they are ignored
+        let host_port = authority.rsplit_once('@').map_or(authority, |(_, hp)| hp);
+        // IPv6 addresses are wrapped in brackets, which also hide their colons
+        let port_sep = match host_port.rfind(']') {
+            Some(end) => host_port[end..].find(':').map(|idx| end + idx),
+            None => host_port.rfind(':'),
+        };
+        let (host, port) = match port_sep {
+            Some(idx) => {
+                let port = &host_port[idx + 1..];
+                let port = port.parse::<u16>().map_err(|e| {
+                    RFSeeError::ParseError(format!("Invalid port {port} in {url}: {e}"))
+                })?;
+                (&host_port[..idx], Some(port))
+            }
+            None => (host_port, None),
+        };
+        if host.is_empty() {
+            return Err(RFSeeError::ParseError(format!(
+                "Invalid URL, missing host: {url}"
+            )));
+        }
+
+        Ok(Self {
+            scheme,
+            host: host.to_ascii_lowercase(),
+            port,
+            path,
+            query,
+        })
+    }
+
+    /// Port to connect to, the explicit one or the default for the scheme
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(self.scheme.default_port())
+    }
+
+    /// Host with the port when it was given explicitly, as sent in the `Host` header
+    pub fn authority(&self) -> String {
+        match self.port {
+            Some(port) => format!("{}:{port}", self.host),
+            None => self.host.clone(),
+        }
+    }
+
+    /// Path and query, as sent in the request line
+    pub fn request_target(&self) -> String {
+        match &self.query {
+            Some(query) => format!("{}?{query}", self.path),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Resolve a reference, such as the `Location` of a redirect which may be relative, against
+    /// this URL
+    pub fn join(&self, reference: &str) -> RFSeeResult<Self> {
+        if reference.contains("://") {
+            return Url::parse(reference);
+        }
+        if let Some(network_path) = reference.strip_prefix("//") {
+            return Url::parse(&format!("{}://{network_path}", self.scheme.as_str()));
+        }
+        let base = format!("{}://{}", self.scheme.as_str(), self.authority());
+        if reference.starts_with('/') {
+            Url::parse(&format!("{base}{reference}"))
+        } else if let Some(query) = reference.strip_prefix('?') {
+            Url::parse(&format!("{base}{}?{query}", self.path))
+        } else {
+            // Relative to the directory of the path
+            let dir = &self.path[..=self.path.rfind('/').unwrap_or(0)];
+            Url::parse(&format!("{base}{dir}{reference}"))
+        }
+    }
+}
+
+impl Display for Url {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}://{}{}",
+            self.scheme.as_str(),
+            self.authority(),
+            self.request_target()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scheme, Url};
+
+    #[test]
+    fn test_parse_url() {
+        let url = Url::parse("http://localhost:8080/rfc/rfc1.txt?x=y#top").unwrap();
+        assert_eq!(url.scheme, Scheme::Http);
+        assert_eq!(url.host, "localhost");
+        assert_eq!(url.port, Some(8080));
+        assert_eq!(url.path, "/rfc/rfc1.txt");
+        assert_eq!(url.query.as_deref(), Some("x=y"));
+        assert_eq!(url.request_target(), "/rfc/rfc1.txt?x=y");
+        assert_eq!(url.to_string(), "http://localhost:8080/rfc/rfc1.txt?x=y");
+
+        let url = Url::parse("HTTPS://www.RFC-Editor.org").unwrap();
+        assert_eq!(url.scheme, Scheme::Https);
+        assert_eq!(url.host, "www.rfc-editor.org");
+        assert_eq!(url.port(), 443);
+        assert_eq!(url.path, "/");
+        assert_eq!(url.authority(), "www.rfc-editor.org");
+
+        let url = Url::parse("http://[::1]:8080/").unwrap();
+        assert_eq!(url.host, "[::1]");
+        assert_eq!(url.port, Some(8080));
+    }
+
+    #[test]
+    fn test_parse_invalid_url() {
+        assert!(Url::parse("www.rfc-editor.org/rfc/rfc1.txt").is_err());
+        assert!(Url::parse("ftp://ftp.rfc-editor.org/in-notes/rfc1.txt").is_err());
+        assert!(Url::parse("http://localhost:http/").is_err());
+        assert!(Url::parse("https:///rfc1.txt").is_err());
+    }
+
+    #[test]
+    fn test_join_absolute() {
+        let base = Url::parse("https://www.ietf.org/rfc/rfc-index.txt").unwrap();
+        let joined = base.join("https://www.rfc-editor.org/rfc-index.txt");
+        assert_eq!(
+            joined.unwrap().to_string(),
+            "https://www.rfc-editor.org/rfc-index.txt"
+        );
+        let joined = base.join("//rfc-editor.org/rfc/");
+        assert_eq!(joined.unwrap().to_string(), "https://rfc-editor.org/rfc/");
+    }
+
+    #[test]
+    fn test_join_relative() {
+        let base = Url::parse("http://localhost:8080/rfc/rfc1.txt?x=/y").unwrap();
+        let joined = base.join("/in-notes/rfc1.txt");
+        assert_eq!(
+            joined.unwrap().to_string(),
+            "http://localhost:8080/in-notes/rfc1.txt"
+        );
+        let joined = base.join("rfc1.html");
+        assert_eq!(
+            joined.unwrap().to_string(),
+            "http://localhost:8080/rfc/rfc1.html"
+        );
+        let joined = Url::parse("https://www.rfc-editor.org")
+            .unwrap()
+            .join("rfc/");
+        assert_eq!(
+            joined.unwrap().to_string(),
+            "https://www.rfc-editor.org/rfc/"
+        );
+    }
+}
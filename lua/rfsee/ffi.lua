@@ -2,12 +2,27 @@ local ffi = require("ffi")
 
 ffi.cdef([[
 typedef void (*progress_callback_t)(const char* msg);
-void build_index(progress_callback_t progress_cb);
+
+struct BuildOptions {
+    double requests_per_second;
+    int max_in_flight;
+    const char* index_url;
+    const char* rfc_url_base;
+    const char* result_url_base;
+    const char* stopwords_file;
+    int stem;
+    int scoring;
+    double bm25_k1;
+    double bm25_b;
+};
+
+void build_index(progress_callback_t progress_cb, const struct BuildOptions* options);
 void test_print(progress_callback_t progress_cb);
 
 struct RfcSearchResult {
     const char* url;
     const char* title;
+    const char* annotation;
 };
 
 struct RfcSearchResults {
@@ -16,8 +31,23 @@ struct RfcSearchResults {
     int error;
 };
 
-// The function returning a pointer to RfcSearchResults
-struct RfcSearchResults* search_terms(const char* terms);
+struct SearchFilterOptions {
+    const char* statuses;
+    const char* exclude_statuses;
+    const char* streams;
+    int from_year;
+    int to_year;
+    int hide_obsoleted;
+    double obsoleted_penalty;
+};
+
+// The functions returning a pointer to RfcSearchResults
+struct RfcSearchResults* search_terms(const char* terms, const char* result_url_base);
+struct RfcSearchResults* search_terms_filtered(
+    const char* terms,
+    const char* result_url_base,
+    const struct SearchFilterOptions* filter
+);
 ]])
 
 local script_dir = vim.fn.expand("<sfile>:p:h:h")
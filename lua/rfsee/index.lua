@@ -8,8 +8,31 @@ local M        = {}
 RFC_INDEX_URL  = "https://www.ietf.org/rfc/rfc-index.txt"
 RFC_DELIMITTER = "\n\n"
 
-function M.search_terms(terms)
-    local results = lib.search_terms(terms)
+-- Join a list of filter values into the comma separated string the library expects
+local function filter_list(values)
+    if values == nil or #values == 0 then
+        return nil
+    end
+    return table.concat(values, ",")
+end
+
+function M.search_terms(terms, opts)
+    opts = opts or {}
+    local filter = opts.filter or {}
+    -- Keep the strings referenced so they aren't collected while the struct points at them
+    local statuses = filter_list(filter.statuses)
+    local exclude_statuses = filter_list(filter.exclude_statuses)
+    local streams = filter_list(filter.streams)
+    local filter_options = ffi.new("struct SearchFilterOptions", {
+        statuses = statuses,
+        exclude_statuses = exclude_statuses,
+        streams = streams,
+        from_year = filter.from_year or 0,
+        to_year = filter.to_year or 0,
+        hide_obsoleted = filter.hide_obsoleted and 1 or 0,
+        obsoleted_penalty = filter.obsoleted_penalty or 0,
+    })
+    local results = lib.search_terms_filtered(terms, opts.result_url_base, filter_options)
 
     -- Check for errors
     if results == nil or results.error ~= 0 then
@@ -24,6 +47,9 @@ function M.search_terms(terms)
         -- Convert C strings to Lua strings and remove any newlines.
         local title = ffi.string(rfc.title):gsub("\n", " ")
         local url = ffi.string(rfc.url):gsub("\n", " ")
+        if rfc.annotation ~= nil then
+            title = title .. " (" .. ffi.string(rfc.annotation) .. ")"
+        end
         table.insert(lines, title .. " - " .. url)
     end
 
@@ -61,7 +87,8 @@ end
 
 -- Our Lua callback, cast to a C function pointer
 
-function M.refresh()
+function M.refresh(opts)
+    opts = opts or {}
     local start_time = os.clock()
     local buf, win = window.create_progress_window()
     window.update_progress_window(buf, "Building RFC index")
@@ -73,7 +100,20 @@ function M.refresh()
 
     local progress_cb_c = ffi.cast("progress_callback_t", progress_cb)
 
-    lib.build_index(progress_cb_c)
+    local options = ffi.new("struct BuildOptions", {
+        requests_per_second = opts.requests_per_second or 0,
+        max_in_flight = opts.max_in_flight or 0,
+        index_url = opts.index_url,
+        rfc_url_base = opts.rfc_url_base,
+        result_url_base = opts.result_url_base,
+        stopwords_file = opts.stopwords_file,
+        stem = opts.stem and 1 or 0,
+        scoring = opts.scoring == "bm25" and 1 or 0,
+        bm25_k1 = opts.bm25_k1 or -1,
+        bm25_b = opts.bm25_b or -1,
+    })
+
+    lib.build_index(progress_cb_c, options)
     local end_time = os.clock()
     window.update_progress_window(buf, string.format("Built RFC index", end_time - start_time))
     -- Brief pause before closing
@@ -2,13 +2,18 @@ local M = {}
 
 local index = require("rfsee.index")
 
----@diagnostic disable-next-line: unused-local
 function M.setup(opts)
-    vim.api.nvim_create_user_command("RFSeeIndex", index.refresh, {})
+    opts = opts or {}
+    vim.api.nvim_create_user_command(
+        "RFSeeIndex",
+        function()
+            index.refresh(opts)
+        end,
+        {})
     vim.api.nvim_create_user_command(
         "RFSee",
-        function(opts)
-            index.search_terms(opts.args)
+        function(cmd)
+            index.search_terms(cmd.args, opts)
         end,
         {
             nargs = 1
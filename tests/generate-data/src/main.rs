@@ -1,6 +1,6 @@
 use std::ffi::c_char;
 
-use rfsee_tf_idf::{RfcEntry, TfIdf};
+use rfsee_tf_idf::{RfcDetails, RfcEntry, TfIdf};
 
 extern "C" fn dummy_cb(_msg: *const c_char) {}
 
@@ -9,13 +9,19 @@ fn main() {
     let rfc1 = RfcEntry {
         number: 1,
         url: "https://rfsee.com/1".to_string(),
-        title: "Test 1".to_string(),
+        details: RfcDetails {
+            title: "Test 1".to_string(),
+            ..Default::default()
+        },
         content: Some("Hello world".to_string()),
     };
     let rfc2 = RfcEntry {
         number: 2,
         url: "https://rfsee.com/2".to_string(),
-        title: "Test 2".to_string(),
+        details: RfcDetails {
+            title: "Test 2".to_string(),
+            ..Default::default()
+        },
         content: Some("Goodbye car".to_string()),
     };
 
@@ -24,5 +30,5 @@ fn main() {
 
     tf_idf.finish(dummy_cb);
     let path = rfsee_tf_idf::get_index_path(None).unwrap();
-    tf_idf.save(&path);
+    tf_idf.save(&path).unwrap();
 }
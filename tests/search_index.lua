@@ -5,6 +5,7 @@ ffi.cdef([[
 struct RfcSearchResult {
     const char* url;
     const char* title;
+    const char* annotation;
 };
 
 struct RfcSearchResults {
@@ -14,7 +15,7 @@ struct RfcSearchResults {
 };
 
 // The function returning a pointer to RfcSearchResults
-struct RfcSearchResults* search_terms(const char* terms);
+struct RfcSearchResults* search_terms(const char* terms, const char* result_url_base);
 ]])
 
 local current_dir = lfs.currentdir();
@@ -22,7 +23,7 @@ local lib_extension = (jit.os == "OSX") and ".dylib" or ".so"
 local dylib = current_dir .. "/artifacts/libffi" .. lib_extension
 local lib = ffi.load(dylib)
 
-local results = lib.search_terms("Hello")
+local results = lib.search_terms("Hello", nil)
 print("Results: ", results.error)
 
 -- Convert RFC results into lines